@@ -1,13 +1,31 @@
 use crate::{
-    color_math::{generate_color, generate_palette, generate_palette_from_base, monochromatic},
+    args::Args,
+    color_distance,
+    color_math::{
+        Harmony, PaletteRng, WorkingSpace, generate_color, harmony, monochromatic, tonal_scale,
+        variations,
+    },
     color_spaces::Color as dis_color,
     error::PaletteError,
-    file::{list_palette_names, load_palette, save_palette},
+    file::{
+        export_tokens, list_dictionary_names, list_palette_names, load_dictionary, load_palette,
+        save_palette,
+    },
     mode::{RetryAction, UiMode},
-    ui::{draw_error_popup, draw_open_popup, draw_save_popup},
+    named_colors,
+    strategy::{HarmonyStrategy, PaletteStrategy, RandomHarmony, generate_distinct, strategies},
+    terminal::ColorSupport,
+    ui::{
+        Backdrop, NameStyle, draw_contrast_popup, draw_edit_popup, draw_error_popup,
+        draw_export_popup, draw_open_popup, draw_save_popup,
+    },
 };
 use ratatui::{DefaultTerminal, Frame};
-use std::io;
+use std::{io, sync::Arc};
+
+/// How many tints, shades and tones the explorer starts with, and the most it shows.
+pub const EXPLORER_STEPS: usize = 3;
+pub const MAX_EXPLORER_STEPS: usize = 8;
 
 #[derive(Debug, Default)]
 pub struct App {
@@ -20,6 +38,18 @@ pub struct App {
     pub num_locked: u8,
     pub mode: UiMode,
     pub all_palette_names: Vec<String>,
+    pub backdrop: Backdrop,
+    pub name_style: NameStyle,
+    pub color_support: ColorSupport,
+    pub working_space: WorkingSpace,
+    pub args: Args,
+    /// What the palette was last generated from, none for one that was loaded.
+    pub seed: Option<u64>,
+    pub rng: PaletteRng,
+    /// Position in `strategies()` of the one new palettes are made with.
+    pub strategy: usize,
+    /// The harmony rule last applied to the selected column.
+    pub harmony: Option<Harmony>,
 }
 
 impl App {
@@ -34,6 +64,18 @@ impl App {
             draw_save_popup(frame, input);
         }
 
+        if let UiMode::Contrast { apca } = self.mode {
+            draw_contrast_popup(frame, &self.colors, apca, self.color_support);
+        }
+
+        if let UiMode::Export { input } = &self.mode {
+            draw_export_popup(frame, input);
+        }
+
+        if let UiMode::Edit { input } = &self.mode {
+            draw_edit_popup(frame, input);
+        }
+
         if let UiMode::Open {
             input,
             matches,
@@ -71,12 +113,121 @@ impl App {
     }
 
     fn startup(&mut self) -> Result<Vec<dis_color>, PaletteError> {
-        let start_palette = match load_palette("cache") {
-            Ok(palette) => palette,
-            Err(_) => generate_palette(5)?,
+        // a seed or size on the command line asks for that palette, not the last one
+        if self.args == Args::default()
+            && let Ok(palette) = load_palette("cache")
+        {
+            return Ok(palette);
+        }
+
+        if let Some(name) = &self.args.strategy {
+            self.strategy = strategies()
+                .iter()
+                .position(|strategy| strategy.name().eq_ignore_ascii_case(name))
+                .ok_or_else(|| PaletteError::InvalidArgument(format!("no strategy '{name}'")))?;
+        }
+        self.reseed(self.args.seed.unwrap_or_else(rand::random));
+        generate_distinct(
+            self.palette_strategy().as_ref(),
+            &[],
+            self.args.size.unwrap_or(5),
+            self.working_space,
+            &mut self.rng,
+        )
+    }
+
+    pub(crate) fn palette_strategy(&self) -> Arc<dyn PaletteStrategy> {
+        strategies()
+            .get(self.strategy)
+            .cloned()
+            .unwrap_or_else(|| Arc::new(RandomHarmony))
+    }
+
+    /// Lists the partners of the selected color to choose from.
+    pub(crate) fn enter_harmony_mode(&mut self) -> Result<(), PaletteError> {
+        let Some(color) = self.colors.get(self.selected) else {
+            return Ok(());
+        };
+        let mut options = Vec::new();
+        for rule in Harmony::SUGGESTED {
+            for partner in harmony(color, rule, self.working_space)? {
+                options.push((rule, partner));
+            }
+        }
+        self.mode = UiMode::Harmony {
+            column: self.selected,
+            options,
+            selected: 0,
+        };
+        Ok(())
+    }
+
+    /// Lays out `steps` tints, shades and tones of the selected color, starting on it.
+    pub(crate) fn enter_explorer(&mut self, steps: usize) -> Result<(), PaletteError> {
+        let Some(color) = self.colors.get(self.selected) else {
+            return Ok(());
+        };
+        let steps = steps.clamp(1, MAX_EXPLORER_STEPS);
+        let grid = variations(color, steps)?;
+        self.mode = UiMode::Explorer {
+            column: self.selected,
+            grid,
+            row: steps,
+            col: 0,
+            steps,
         };
+        Ok(())
+    }
 
-        Ok(start_palette)
+    /// Shows the tonal scale of the selected color, starting on the color itself.
+    pub(crate) fn enter_scale(&mut self) -> Result<(), PaletteError> {
+        let Some(color) = self.colors.get(self.selected) else {
+            return Ok(());
+        };
+        let scale = tonal_scale(color)?;
+        let selected = scale
+            .iter()
+            .position(|(_, step)| step == color)
+            .unwrap_or(0);
+        self.mode = UiMode::Scale {
+            column: self.selected,
+            scale,
+            selected,
+        };
+        Ok(())
+    }
+
+    /// Recolors the unlocked columns with the partners `rule` gives the selected color,
+    /// which stays as it is.
+    pub(crate) fn apply_harmony(&mut self, rule: Harmony) -> Result<(), PaletteError> {
+        let Some(anchor) = self.colors.get(self.selected).cloned() else {
+            return Ok(());
+        };
+        let open: Vec<usize> = (0..self.colors.len())
+            .filter(|&i| i != self.selected && !self.colors[i].locked)
+            .collect();
+        let partners = HarmonyStrategy(rule).generate(
+            &[anchor],
+            open.len(),
+            self.working_space,
+            &mut self.rng,
+        )?;
+        for (i, color) in open.into_iter().zip(partners) {
+            self.colors[i] = color;
+        }
+        self.harmony = Some(rule);
+        self.seed = None;
+        Ok(())
+    }
+
+    pub(crate) fn cycle_strategy(&mut self) {
+        self.strategy = (self.strategy + 1) % strategies().len();
+    }
+
+    /// Starts generating from `seed`, so what comes next can be made again from it.
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+        self.rng = PaletteRng::seeded(seed);
     }
 
     fn shutdown(&mut self, palette: Vec<dis_color>) {
@@ -89,6 +240,30 @@ impl App {
         }
     }
 
+    /// Moves on to the next name dictionary and renames the palette with it.
+    pub(crate) fn cycle_dictionary(&mut self) -> Result<(), PaletteError> {
+        let names = list_dictionary_names()?;
+        let current = &named_colors::active().name;
+        let next = names
+            .iter()
+            .position(|name| name == current)
+            .map_or(0, |i| (i + 1) % names.len());
+
+        named_colors::set_active(load_dictionary(&names[next])?);
+        for color in self.colors.iter_mut() {
+            color.forget_name();
+        }
+        Ok(())
+    }
+
+    /// Moves on to the next distance metric and renames the palette with it.
+    pub(crate) fn cycle_metric(&mut self) {
+        color_distance::set_metric(color_distance::metric().next());
+        for color in self.colors.iter_mut() {
+            color.forget_name();
+        }
+    }
+
     pub fn retry(&mut self) {
         if let Some(action) = self.retry_action.clone() {
             self.error = None;
@@ -107,28 +282,28 @@ impl App {
                         self.retry_action = Some(RetryAction::Save(palette));
                     }
                 }
-                RetryAction::Generate(size) => {
-                    if let Err(e) = generate_palette(size) {
-                        self.error = Some(e);
-                        self.retry_action = Some(RetryAction::Generate(size));
-                    }
-                }
                 RetryAction::GenerateFrom(palette, size) => {
-                    if let Err(e) = generate_palette_from_base(&palette, size) {
+                    if let Err(e) = generate_distinct(
+                        self.palette_strategy().as_ref(),
+                        &palette,
+                        size,
+                        self.working_space,
+                        &mut self.rng,
+                    ) {
                         self.error = Some(e);
                         self.retry_action = Some(RetryAction::GenerateFrom(palette, size));
                     }
                 }
                 RetryAction::GenerateSingle => {
-                    if let Err(e) = generate_color() {
+                    if let Err(e) = generate_color(self.working_space, &mut self.rng) {
                         self.error = Some(e);
                         self.retry_action = Some(RetryAction::GenerateSingle);
                     }
                 }
-                RetryAction::Monochrome(hsl) => {
-                    if let Err(e) = monochromatic(&hsl) {
+                RetryAction::Monochrome(color) => {
+                    if let Err(e) = monochromatic(&color, self.working_space) {
                         self.error = Some(e);
-                        self.retry_action = Some(RetryAction::Monochrome(hsl));
+                        self.retry_action = Some(RetryAction::Monochrome(color));
                     }
                 }
                 RetryAction::Load(name) => {
@@ -137,6 +312,42 @@ impl App {
                         self.retry_action = Some(RetryAction::Load(name))
                     }
                 }
+                RetryAction::Harmony(rule) => {
+                    if let Err(e) = self.apply_harmony(rule) {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Harmony(rule))
+                    }
+                }
+                RetryAction::Partners => {
+                    if let Err(e) = self.enter_harmony_mode() {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Partners)
+                    }
+                }
+                RetryAction::Explorer(steps) => {
+                    if let Err(e) = self.enter_explorer(steps) {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Explorer(steps))
+                    }
+                }
+                RetryAction::Scale => {
+                    if let Err(e) = self.enter_scale() {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Scale)
+                    }
+                }
+                RetryAction::Export(name) => {
+                    if let Err(e) = export_tokens(&name, &self.colors) {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Export(name))
+                    }
+                }
+                RetryAction::Dictionary => {
+                    if let Err(e) = self.cycle_dictionary() {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Dictionary)
+                    }
+                }
                 RetryAction::List => {
                     if let Err(e) = list_palette_names() {
                         self.error = Some(e);
@@ -153,18 +364,276 @@ mod tests {
     use crate::input::TextInput;
 
     use super::*;
+    use crate::color_math::{generate_palette, harmony};
+    use crate::color_spaces::Rgb;
     use crossterm::event::{KeyCode, KeyEvent};
 
     #[test]
     fn pressing_s_enters_save() {
         let mut app = App {
-            colors: generate_palette(5).unwrap(),
+            colors: generate_palette(5, WorkingSpace::default(), &mut PaletteRng::default())
+                .unwrap(),
             ..Default::default()
         };
         app.handle_key_event(KeyEvent::from(KeyCode::Char('s')));
         assert!(matches!(app.mode, UiMode::Save { .. }));
     }
 
+    #[test]
+    fn seed_and_size_remake_the_palette() {
+        let args = Args {
+            seed: Some(12345),
+            size: Some(4),
+            strategy: None,
+        };
+        let mut first = App {
+            args: args.clone(),
+            ..Default::default()
+        };
+        let mut again = App {
+            args,
+            ..Default::default()
+        };
+        let palette = first.startup().unwrap();
+        assert_eq!(palette.len(), 4);
+        assert_eq!(palette, again.startup().unwrap());
+        assert_eq!(first.seed, Some(12345));
+
+        // a new palette gets a new seed, which makes it again
+        first.colors = palette;
+        first.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        let seed = first.seed.unwrap();
+        again.reseed(seed);
+        assert_eq!(
+            first.colors,
+            generate_palette(4, WorkingSpace::default(), &mut again.rng).unwrap()
+        );
+    }
+
+    #[test]
+    fn changing_colors_forgets_the_seed() {
+        type Handler = fn(&mut App, KeyEvent);
+        let pick: [(char, Handler, KeyCode); 8] = [
+            ('r', App::handle_key_event, KeyCode::Null),
+            ('H', App::handle_key_event, KeyCode::Null),
+            ('m', App::handle_monochrome_key_event, KeyCode::Enter),
+            ('p', App::handle_harmony_key_event, KeyCode::Enter),
+            ('p', App::handle_harmony_key_event, KeyCode::Char('a')),
+            ('t', App::handle_explorer_key_event, KeyCode::Enter),
+            ('T', App::handle_scale_key_event, KeyCode::Enter),
+            ('L', App::handle_key_event, KeyCode::Char(' ')),
+        ];
+
+        for (key, handler, then) in pick {
+            println!("input: {key} then {then:?}, desired result: no seed");
+            let mut app = App {
+                args: Args {
+                    seed: Some(12345),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            app.colors = app.startup().unwrap();
+            assert_eq!(app.seed, Some(12345));
+            app.handle_key_event(KeyEvent::from(KeyCode::Char(key)));
+            handler(&mut app, KeyEvent::from(then));
+            assert_eq!(app.seed, None);
+        }
+
+        let mut app = App {
+            args: Args {
+                seed: Some(12345),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        app.colors = app.startup().unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('e')));
+        for c in "#cd5c5c".chars() {
+            app.handle_edit_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_edit_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.seed, None);
+
+        // a whole new palette is seeded again
+        app.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        assert!(app.seed.is_some());
+    }
+
+    #[test]
+    fn space_keeps_locked_colors_and_uses_the_strategy() {
+        let mut app = App {
+            args: Args {
+                strategy: Some("pastel".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        app.colors = app.startup().unwrap();
+        assert_eq!(app.palette_strategy().name(), "Pastel");
+
+        app.selected = 2;
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('L')));
+        let locked = app.colors[2].clone();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char(' ')));
+        assert_eq!(app.colors.len(), 5);
+        assert_eq!(app.colors[2], locked);
+        // pastels are light
+        assert!(app.colors.iter().all(|color| color.hsl().l >= 780));
+    }
+
+    #[test]
+    fn harmony_recolors_only_unlocked_columns() {
+        let mut app = App {
+            colors: generate_palette(4, WorkingSpace::default(), &mut PaletteRng::seeded(3))
+                .unwrap(),
+            selected: 1,
+            ..Default::default()
+        };
+        app.colors[3].locked = true;
+        let (selected, locked) = (app.colors[1].clone(), app.colors[3].clone());
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('H')));
+        assert_eq!(app.harmony, Some(Harmony::Complementary));
+        assert_eq!((&app.colors[1], &app.colors[3]), (&selected, &locked));
+        let complement = harmony(&selected, Harmony::Complementary, app.working_space).unwrap();
+        assert_eq!(app.colors[0], complement[0]);
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('H')));
+        assert_eq!(app.harmony, Some(Harmony::Analogous { spread: 30 }));
+    }
+
+    #[test]
+    fn harmony_mode_offers_partners() {
+        let mut app = App {
+            colors: generate_palette(4, WorkingSpace::default(), &mut PaletteRng::seeded(5))
+                .unwrap(),
+            selected: 2,
+            ..Default::default()
+        };
+        let color = app.colors[2].clone();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        let UiMode::Harmony { options, .. } = &app.mode else {
+            panic!("expected Harmony mode");
+        };
+        // complement, two of a triad, split complement and analogous, three of a square
+        assert_eq!(options.len(), 10);
+        let triad = harmony(&color, Harmony::Triadic, app.working_space).unwrap();
+        assert_eq!(options[2], (Harmony::Triadic, triad[1].clone()));
+
+        app.handle_harmony_key_event(KeyEvent::from(KeyCode::Char('j')));
+        app.handle_harmony_key_event(KeyEvent::from(KeyCode::Char('j')));
+        app.handle_harmony_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert_eq!(app.colors[2], triad[1]);
+
+        // the whole triad, from the color now in the column
+        let triad = harmony(&app.colors[2], Harmony::Triadic, app.working_space).unwrap();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        app.handle_harmony_key_event(KeyEvent::from(KeyCode::Char('j')));
+        app.handle_harmony_key_event(KeyEvent::from(KeyCode::Char('a')));
+        assert_eq!(app.harmony, Some(Harmony::Triadic));
+        assert_eq!((&app.colors[0], &app.colors[1]), (&triad[0], &triad[1]));
+    }
+
+    #[test]
+    fn explorer_picks_from_the_grid() {
+        let mut app = App {
+            colors: generate_palette(3, WorkingSpace::default(), &mut PaletteRng::seeded(9))
+                .unwrap(),
+            ..Default::default()
+        };
+        let grid = variations(&app.colors[0], EXPLORER_STEPS + 1).unwrap();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('t')));
+        app.handle_explorer_key_event(KeyEvent::from(KeyCode::Char('+')));
+        let UiMode::Explorer { row, col, .. } = app.mode else {
+            panic!("expected Explorer mode");
+        };
+        // starts on the color itself
+        assert_eq!((row, col), (EXPLORER_STEPS + 1, 0));
+
+        for key in ['k', 'l', 'l', 'h'] {
+            app.handle_explorer_key_event(KeyEvent::from(KeyCode::Char(key)));
+        }
+        app.handle_explorer_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert_eq!(app.colors[0], grid[EXPLORER_STEPS][1]);
+    }
+
+    #[test]
+    fn scale_mode_starts_on_the_color() {
+        let mut app = App {
+            colors: vec![dis_color::new(Rgb::new(205, 92, 92).encode()).unwrap()],
+            ..Default::default()
+        };
+        let scale = tonal_scale(&app.colors[0]).unwrap();
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('T')));
+        let UiMode::Scale { selected, .. } = app.mode else {
+            panic!("expected Scale mode");
+        };
+        assert_eq!(scale[selected].0, 500);
+
+        app.handle_scale_key_event(KeyEvent::from(KeyCode::Char('k')));
+        app.handle_scale_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(app.colors[0], scale[selected - 1].1);
+    }
+
+    #[test]
+    fn harmonies_leave_an_empty_palette_alone() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('p')));
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('H')));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert!(app.error.is_none());
+        assert_eq!(app.harmony, None);
+    }
+
+    #[test]
+    fn explorer_leaves_an_empty_palette_alone() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('t')));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert!(app.error.is_none());
+    }
+
+    #[test]
+    fn scale_leaves_an_empty_palette_alone() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('T')));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert!(app.error.is_none());
+    }
+
+    #[test]
+    fn editing_an_empty_palette_does_nothing() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('e')));
+        for c in "teal".chars() {
+            app.handle_edit_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_edit_event(KeyEvent::from(KeyCode::Enter));
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert!(app.colors.is_empty());
+    }
+
+    #[test]
+    fn contrast_mode_switches_measures() {
+        let mut app = App::default();
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('C')));
+        assert!(matches!(app.mode, UiMode::Contrast { apca: false }));
+
+        app.handle_contrast_key_event(KeyEvent::from(KeyCode::Char('a')));
+        assert!(matches!(app.mode, UiMode::Contrast { apca: true }));
+        app.handle_contrast_key_event(KeyEvent::from(KeyCode::Char('A')));
+        assert!(matches!(app.mode, UiMode::Contrast { apca: false }));
+
+        app.handle_contrast_key_event(KeyEvent::from(KeyCode::Esc));
+        assert!(matches!(app.mode, UiMode::Normal));
+    }
+
     #[test]
     fn typing_in_save_mode_updates_input() {
         let mut app = App {
@@ -180,4 +649,39 @@ mod tests {
             panic!("expected Save mode");
         }
     }
+
+    #[test]
+    fn editing_replaces_selected_color() {
+        let mut app = App {
+            colors: generate_palette(3, WorkingSpace::default(), &mut PaletteRng::default())
+                .unwrap(),
+            selected: 1,
+            ..Default::default()
+        };
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('e')));
+        for c in "#cd5c5c".chars() {
+            app.handle_edit_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_edit_event(KeyEvent::from(KeyCode::Enter));
+
+        assert!(matches!(app.mode, UiMode::Normal));
+        assert_eq!(app.colors[1].name(), "Indian Red");
+    }
+
+    #[test]
+    fn editing_in_display_p3_flags_out_of_gamut() {
+        let mut app = App {
+            colors: generate_palette(3, WorkingSpace::default(), &mut PaletteRng::default())
+                .unwrap(),
+            ..Default::default()
+        };
+        app.handle_key_event(KeyEvent::from(KeyCode::Char('e')));
+        for c in "color(display-p3 0 1 0)".chars() {
+            app.handle_edit_event(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.handle_edit_event(KeyEvent::from(KeyCode::Enter));
+
+        assert!(app.error.is_none());
+        assert!(!app.colors[0].in_srgb_gamut());
+    }
 }
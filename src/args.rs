@@ -0,0 +1,113 @@
+use crate::{error::PaletteError, strategy};
+
+pub const USAGE: &str = "usage: palette_maker [--seed <u64>] [--size <count>] [--strategy <name>]";
+
+/// What the palette is started from. With neither given the last session's
+/// palette is loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Args {
+    pub seed: Option<u64>,
+    pub size: Option<usize>,
+    pub strategy: Option<String>,
+}
+
+impl Args {
+    /// Reads `--seed 12345`, `--size 5` and `--strategy pastel`, or `--seed=12345`.
+    /// The program name should already be skipped.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Args, PaletteError> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let value = || {
+                inline
+                    .or_else(|| args.next())
+                    .ok_or_else(|| PaletteError::InvalidArgument(format!("{flag} needs a value")))
+            };
+            match flag.as_str() {
+                "--seed" => parsed.seed = Some(value()?.parse()?),
+                "--size" => match value()?.parse()? {
+                    0 => {
+                        return Err(PaletteError::InvalidArgument(
+                            "--size must be at least 1".to_string(),
+                        ));
+                    }
+                    size => parsed.size = Some(size),
+                },
+                "--strategy" => {
+                    let name = value()?;
+                    if strategy::find(&name).is_none() {
+                        return Err(PaletteError::InvalidArgument(format!(
+                            "no strategy '{name}'"
+                        )));
+                    }
+                    parsed.strategy = Some(name);
+                }
+                _ => return Err(PaletteError::InvalidArgument(flag)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Args, PaletteError> {
+        Args::parse(line.split_whitespace().map(String::from))
+    }
+
+    #[test]
+    fn test_parse() {
+        let tests = [
+            ("", Args::default()),
+            (
+                "--seed 12345 --size 5",
+                Args {
+                    seed: Some(12345),
+                    size: Some(5),
+                    strategy: None,
+                },
+            ),
+            (
+                "--size=3",
+                Args {
+                    seed: None,
+                    size: Some(3),
+                    strategy: None,
+                },
+            ),
+            (
+                "--strategy=monochrome --seed 1",
+                Args {
+                    seed: Some(1),
+                    size: None,
+                    strategy: Some("monochrome".to_string()),
+                },
+            ),
+        ];
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match parse(input) {
+                Ok(args) => assert_eq!(args, expected),
+                Err(e) => panic!("{e}"),
+            }
+        }
+
+        for input in [
+            "--seed",
+            "--seed red",
+            "--size 0",
+            "--colors 5",
+            "--strategy plaid",
+        ] {
+            println!("input: {:?}", input);
+            assert!(parse(input).is_err());
+        }
+    }
+}
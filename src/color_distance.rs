@@ -0,0 +1,283 @@
+use crate::{
+    color_math::three_node_distance_rgb,
+    color_spaces::{Color, Lab, Oklab, Rgb},
+    error::PaletteError,
+};
+use std::{
+    fmt,
+    sync::{LazyLock, RwLock},
+};
+
+static METRIC: LazyLock<RwLock<DistanceMetric>> =
+    LazyLock::new(|| RwLock::new(DistanceMetric::default()));
+
+/// How "far apart" two colors are. The perceptual metrics all report on
+/// roughly the same 0-100 scale, where ~2.3 is a just noticeable difference.
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum DistanceMetric {
+    Rgb,
+    Cie76,
+    Cie94,
+    #[default]
+    Ciede2000,
+    Oklab,
+}
+
+impl DistanceMetric {
+    pub fn next(self) -> DistanceMetric {
+        match self {
+            DistanceMetric::Rgb => DistanceMetric::Cie76,
+            DistanceMetric::Cie76 => DistanceMetric::Cie94,
+            DistanceMetric::Cie94 => DistanceMetric::Ciede2000,
+            DistanceMetric::Ciede2000 => DistanceMetric::Oklab,
+            DistanceMetric::Oklab => DistanceMetric::Rgb,
+        }
+    }
+
+    pub fn distance_rgb(&self, rgb1: &Rgb, rgb2: &Rgb) -> Result<f32, PaletteError> {
+        match self {
+            DistanceMetric::Rgb => Ok(rgb_distance(rgb1, rgb2)),
+            DistanceMetric::Oklab => Ok(ok_distance(
+                &rgb1.encode().get_oklab()?,
+                &rgb2.encode().get_oklab()?,
+            )),
+            DistanceMetric::Cie76 | DistanceMetric::Cie94 | DistanceMetric::Ciede2000 => {
+                let lab1 = rgb1.encode().lab_f32()?;
+                let lab2 = rgb2.encode().lab_f32()?;
+                Ok(match self {
+                    DistanceMetric::Cie76 => delta_e_76(lab1, lab2),
+                    DistanceMetric::Cie94 => delta_e_94(lab1, lab2),
+                    _ => delta_e_2000(lab1, lab2),
+                })
+            }
+        }
+    }
+}
+
+impl fmt::Display for DistanceMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistanceMetric::Rgb => write!(f, "RGB"),
+            DistanceMetric::Cie76 => write!(f, "CIE76"),
+            DistanceMetric::Cie94 => write!(f, "CIE94"),
+            DistanceMetric::Ciede2000 => write!(f, "CIEDE2000"),
+            DistanceMetric::Oklab => write!(f, "OKLab"),
+        }
+    }
+}
+
+/// The metric colors are named and deduplicated with.
+pub fn metric() -> DistanceMetric {
+    match METRIC.read() {
+        Ok(metric) => *metric,
+        Err(poisoned) => *poisoned.into_inner(),
+    }
+}
+
+pub fn set_metric(metric: DistanceMetric) {
+    match METRIC.write() {
+        Ok(mut current) => *current = metric,
+        Err(poisoned) => *poisoned.into_inner() = metric,
+    }
+}
+
+pub fn distance(
+    color1: &Color,
+    color2: &Color,
+    metric: DistanceMetric,
+) -> Result<f32, PaletteError> {
+    metric.distance_rgb(color1.rgb(), color2.rgb())
+}
+
+/// Keeps the first of any run of colors closer than `threshold` to one already kept.
+pub fn dedupe(
+    colors: &[Color],
+    metric: DistanceMetric,
+    threshold: f32,
+) -> Result<Vec<Color>, PaletteError> {
+    let mut kept: Vec<Color> = Vec::with_capacity(colors.len());
+    for color in colors {
+        let mut unique = true;
+        for other in &kept {
+            if distance(color, other, metric)? < threshold {
+                unique = false;
+                break;
+            }
+        }
+        if unique {
+            kept.push(color.clone());
+        }
+    }
+    Ok(kept)
+}
+
+pub fn rgb_distance(rgb1: &Rgb, rgb2: &Rgb) -> f32 {
+    (three_node_distance_rgb(rgb1.clone(), rgb2.clone()) as f32).sqrt()
+}
+
+pub fn ok_distance(oklab1: &Oklab, oklab2: &Oklab) -> f32 {
+    let l = (oklab1.l as f32 - oklab2.l as f32) / 10.0;
+    let a = (oklab1.a as f32 - oklab2.a as f32) / 10.0;
+    let b = (oklab1.b as f32 - oklab2.b as f32) / 10.0;
+    (l * l + a * a + b * b).sqrt()
+}
+
+pub fn cie76(lab1: &Lab, lab2: &Lab) -> f32 {
+    delta_e_76(lab_to_f32(lab1), lab_to_f32(lab2))
+}
+
+pub fn cie94(lab1: &Lab, lab2: &Lab) -> f32 {
+    delta_e_94(lab_to_f32(lab1), lab_to_f32(lab2))
+}
+
+pub fn ciede2000(lab1: &Lab, lab2: &Lab) -> f32 {
+    delta_e_2000(lab_to_f32(lab1), lab_to_f32(lab2))
+}
+
+// -----------------------
+
+fn lab_to_f32(lab: &Lab) -> (f32, f32, f32) {
+    (
+        lab.l as f32 / 10.0,
+        lab.a as f32 / 10.0,
+        lab.b as f32 / 10.0,
+    )
+}
+
+fn delta_e_76((l1, a1, b1): (f32, f32, f32), (l2, a2, b2): (f32, f32, f32)) -> f32 {
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+// graphic arts weightings
+fn delta_e_94((l1, a1, b1): (f32, f32, f32), (l2, a2, b2): (f32, f32, f32)) -> f32 {
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let delta_l = l1 - l2;
+    let delta_c = c1 - c2;
+    let delta_h_sq = ((a1 - a2).powi(2) + (b1 - b2).powi(2) - delta_c.powi(2)).max(0.0);
+
+    let s_c = 1.0 + 0.045 * c1;
+    let s_h = 1.0 + 0.015 * c1;
+
+    (delta_l.powi(2) + (delta_c / s_c).powi(2) + delta_h_sq / s_h.powi(2)).sqrt()
+}
+
+fn delta_e_2000((l1, a1, b1): (f32, f32, f32), (l2, a2, b2): (f32, f32, f32)) -> f32 {
+    let c_bar = ((a1 * a1 + b1 * b1).sqrt() + (a2 * a2 + b2 * b2).sqrt()) / 2.0;
+    let c_bar7 = c_bar.powi(7);
+    let g = 0.5 * (1.0 - (c_bar7 / (c_bar7 + 25f32.powi(7))).sqrt());
+
+    let a1 = a1 * (1.0 + g);
+    let a2 = a2 * (1.0 + g);
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+
+    let hue = |a: f32, b: f32| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+    let h1 = hue(a1, b1);
+    let h2 = hue(a2, b2);
+
+    let delta_l = l2 - l1;
+    let delta_c = c2 - c1;
+    let delta_h = if c1 * c2 == 0.0 {
+        0.0
+    } else if (h2 - h1).abs() <= 180.0 {
+        h2 - h1
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else {
+        h2 - h1 + 360.0
+    };
+    let delta_h = 2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+
+    let l_bar = (l1 + l2) / 2.0;
+    let c_bar = (c1 + c2) / 2.0;
+    let h_bar = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * (h_bar - 30.0).to_radians().cos()
+        + 0.24 * (2.0 * h_bar).to_radians().cos()
+        + 0.32 * (3.0 * h_bar + 6.0).to_radians().cos()
+        - 0.20 * (4.0 * h_bar - 63.0).to_radians().cos();
+    let delta_theta = 30.0 * (-((h_bar - 275.0) / 25.0).powi(2)).exp();
+    let c_bar7 = c_bar.powi(7);
+    let r_c = 2.0 * (c_bar7 / (c_bar7 + 25f32.powi(7))).sqrt();
+    let s_l = 1.0 + (0.015 * (l_bar - 50.0).powi(2)) / (20.0 + (l_bar - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_bar;
+    let s_h = 1.0 + 0.015 * c_bar * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+
+    ((delta_l / s_l).powi(2)
+        + (delta_c / s_c).powi(2)
+        + (delta_h / s_h).powi(2)
+        + r_t * (delta_c / s_c) * (delta_h / s_h))
+        .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reference pairs from Sharma, Wu & Dalal's CIEDE2000 test data
+    #[test]
+    fn ciede2000_matches_reference_data() {
+        let tests = [
+            ((50.0, 2.6772, -79.7751), (50.0, 0.0, -82.7485), 2.0425),
+            ((50.0, -1.3802, -84.2814), (50.0, 0.0, -82.7485), 1.0),
+            ((50.0, 2.5, 0.0), (50.0, 0.0, -2.5), 4.3065),
+            ((50.0, 2.5, 0.0), (73.0, 25.0, -18.0), 27.1492),
+            (
+                (22.7233, 20.0904, -46.694),
+                (23.0331, 14.973, -42.5619),
+                2.0373,
+            ),
+            ((2.0776, 0.0795, -1.135), (0.9033, -0.0636, -0.5514), 0.9082),
+        ];
+
+        for (lab1, lab2, expected) in tests {
+            let result = delta_e_2000(lab1, lab2);
+            println!(
+                "input: {:?} {:?}, desired result: {expected}, got {result}",
+                lab1, lab2
+            );
+            assert!((result - expected).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn metrics_agree_on_identical_colors() {
+        let rgb = Rgb::new(205, 92, 92);
+        for metric in [
+            DistanceMetric::Rgb,
+            DistanceMetric::Cie76,
+            DistanceMetric::Cie94,
+            DistanceMetric::Ciede2000,
+            DistanceMetric::Oklab,
+        ] {
+            assert_eq!(metric.distance_rgb(&rgb, &rgb).unwrap(), 0.0);
+        }
+    }
+
+    #[test]
+    fn dedupe_drops_near_duplicates() {
+        let colors = vec![
+            Color::new(Rgb::new(205, 92, 92).encode()).unwrap(),
+            Color::new(Rgb::new(206, 92, 93).encode()).unwrap(),
+            Color::new(Rgb::new(70, 130, 180).encode()).unwrap(),
+        ];
+        let deduped = dedupe(&colors, DistanceMetric::Ciede2000, 2.3).unwrap();
+        assert_eq!(deduped, vec![colors[0].clone(), colors[2].clone()]);
+    }
+}
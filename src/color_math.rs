@@ -1,67 +1,288 @@
 use crate::{
-    color_spaces::{Color, Hsl, Rgb},
+    color_spaces::{Color, Hsl, Oklab, Oklch, Rgb},
+    encoding::{
+        ColorSpace, Encoding, KELVIN_MAX, KELVIN_MIN, linear_in_srgb_gamut, srgb8_to_linear,
+    },
     error::PaletteError,
+    strategy::{RandomHarmony, generate_distinct},
 };
 use rand::prelude::*;
+use std::fmt;
 
 // TODO
 
-pub fn complement(hsl: &Hsl) -> Hsl {
-    let new_h = (hsl.h + 180).rem_euclid(360);
-    Hsl::new(new_h, hsl.s, hsl.l)
+/// The HSL-like space colors are generated and stepped through in. HSLuv and HPLuv
+/// keep lightness perceptual, so steps look evenly spaced across hues. OKLCH turns
+/// hues perceptually too, with saturation as a share of the most chroma sRGB can
+/// show at that lightness and hue.
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum WorkingSpace {
+    #[default]
+    Hsl,
+    Hsluv,
+    Hpluv,
+    Oklch,
 }
 
-pub fn triad(hsl: &Hsl) -> (Hsl, Hsl) {
-    let left = (hsl.h as i32 - 120).rem_euclid(360);
-    let right = (hsl.h + 120).rem_euclid(360);
+impl WorkingSpace {
+    pub fn next(self) -> WorkingSpace {
+        match self {
+            WorkingSpace::Hsl => WorkingSpace::Hsluv,
+            WorkingSpace::Hsluv => WorkingSpace::Hpluv,
+            WorkingSpace::Hpluv => WorkingSpace::Oklch,
+            WorkingSpace::Oklch => WorkingSpace::Hsl,
+        }
+    }
 
-    let left = Hsl::new(left as u16, hsl.s, hsl.l);
-    let right = Hsl::new(right, hsl.s, hsl.l);
+    pub fn encode(&self, h: u16, s: u16, l: u16) -> Encoding {
+        match self {
+            WorkingSpace::Hsl => Encoding::Hsl(h, s, l),
+            WorkingSpace::Hsluv => Encoding::Hsluv(h, s, l),
+            WorkingSpace::Hpluv => Encoding::Hpluv(h, s, l),
+            WorkingSpace::Oklch => {
+                let mut c = (s as u32 * max_srgb_chroma(l, h) as u32 / 1000) as u16;
+                // a and b are rounded to whole steps, so at the very edge of the gamut
+                // a little less chroma can land just outside it
+                while c > 0 && !oklch_in_srgb(l, c, h) {
+                    c -= 1;
+                }
+                Encoding::Oklch(l, c, h)
+            }
+        }
+    }
 
-    (left, right)
+    pub fn components(&self, color: &Color) -> Result<(u16, u16, u16), PaletteError> {
+        match self {
+            WorkingSpace::Hsl => Ok((color.hsl().h, color.hsl().s, color.hsl().l)),
+            WorkingSpace::Hsluv => {
+                let hsluv = color.rgb().encode().get_hsluv()?;
+                Ok((hsluv.h, hsluv.s, hsluv.l))
+            }
+            WorkingSpace::Hpluv => {
+                let hpluv = color.rgb().encode().get_hpluv()?;
+                Ok((hpluv.h, hpluv.s, hpluv.l))
+            }
+            WorkingSpace::Oklch => {
+                let oklch = color.oklch();
+                let s = match max_srgb_chroma(oklch.l, oklch.h) {
+                    0 => 0,
+                    max => (oklch.c as u32 * 1000 / max as u32).min(1000) as u16,
+                };
+                Ok((oklch.h, s, oklch.l))
+            }
+        }
+    }
 }
 
-pub fn square(hsl: &Hsl) -> (Hsl, Hsl, Hsl) {
-    let left = (hsl.h as i32 - 90).rem_euclid(360);
-    let middle = (hsl.h + 180).rem_euclid(360);
-    let right = (hsl.h + 90).rem_euclid(360);
+// the most chroma (x1000) srgb can show at an oklch lightness and hue
+fn max_srgb_chroma(l: u16, h: u16) -> u16 {
+    let in_gamut = |c| oklch_in_srgb(l, c, h);
+    let (mut low, mut high): (u16, u16) = (0, 400);
+    while low < high {
+        let mid = (low + high).div_ceil(2);
+        if in_gamut(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    low
+}
 
-    let left = Hsl::new(left as u16, hsl.s, hsl.l);
-    let middle = Hsl::new(middle, hsl.s, hsl.l);
-    let right = Hsl::new(right, hsl.s, hsl.l);
+fn oklch_in_srgb(l: u16, c: u16, h: u16) -> bool {
+    Oklch::new(l, c, h)
+        .to_linear_srgb()
+        .is_ok_and(linear_in_srgb_gamut)
+}
 
-    (left, middle, right)
+impl fmt::Display for WorkingSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkingSpace::Hsl => write!(f, "HSL"),
+            WorkingSpace::Hsluv => write!(f, "HSLuv"),
+            WorkingSpace::Hpluv => write!(f, "HPLuv"),
+            WorkingSpace::Oklch => write!(f, "OKLCH"),
+        }
+    }
+}
+
+/// Rules for colors that go together, as turns of hue away from a starting color.
+/// Spreads are in degrees.
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Harmony {
+    Complementary,
+    Analogous {
+        spread: u16,
+    },
+    Triadic,
+    Square,
+    /// Either side of the complement.
+    SplitComplementary {
+        spread: u16,
+    },
+    /// Two complementary pairs `spread` apart.
+    Rectangle {
+        spread: u16,
+    },
+    /// Either side of the color and either side of its complement.
+    DoubleSplitComplementary {
+        spread: u16,
+    },
+    /// A neighbour, the complement and the complement's neighbour turned the other way.
+    Compound {
+        spread: u16,
+    },
+    /// Both neighbours with the complement as an accent.
+    AccentedAnalogous {
+        spread: u16,
+    },
+}
+
+impl Harmony {
+    /// Every rule with its usual spread.
+    pub const ALL: [Harmony; 9] = [
+        Harmony::Complementary,
+        Harmony::Analogous { spread: 30 },
+        Harmony::Triadic,
+        Harmony::Square,
+        Harmony::SplitComplementary { spread: 30 },
+        Harmony::Rectangle { spread: 60 },
+        Harmony::DoubleSplitComplementary { spread: 30 },
+        Harmony::Compound { spread: 30 },
+        Harmony::AccentedAnalogous { spread: 30 },
+    ];
+
+    /// The rules whose partners harmony mode offers to choose from.
+    pub const SUGGESTED: [Harmony; 5] = [
+        Harmony::Complementary,
+        Harmony::Triadic,
+        Harmony::SplitComplementary { spread: 30 },
+        Harmony::Analogous { spread: 30 },
+        Harmony::Square,
+    ];
+
+    /// The rule after this one in `ALL`, with its usual spread.
+    pub fn next(self) -> Harmony {
+        let position = Harmony::ALL
+            .iter()
+            .position(|rule| rule.name() == self.name())
+            .unwrap_or(0);
+        Harmony::ALL[(position + 1) % Harmony::ALL.len()]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Harmony::Complementary => "Complementary",
+            Harmony::Analogous { .. } => "Analogous",
+            Harmony::Triadic => "Triadic",
+            Harmony::Square => "Square",
+            Harmony::SplitComplementary { .. } => "Split complementary",
+            Harmony::Rectangle { .. } => "Rectangle",
+            Harmony::DoubleSplitComplementary { .. } => "Double split complementary",
+            Harmony::Compound { .. } => "Compound",
+            Harmony::AccentedAnalogous { .. } => "Accented analogous",
+        }
+    }
+
+    /// How far round from the starting color each partner is, in degrees.
+    pub fn offsets(&self) -> Vec<u16> {
+        let turn = |degrees: i32| degrees.rem_euclid(360) as u16;
+        match *self {
+            Harmony::Complementary => vec![180],
+            Harmony::Analogous { spread } => {
+                let spread = spread as i32;
+                vec![turn(-spread), turn(spread)]
+            }
+            Harmony::Triadic => vec![240, 120],
+            Harmony::Square => vec![270, 180, 90],
+            Harmony::SplitComplementary { spread } => {
+                let spread = spread as i32;
+                vec![turn(180 - spread), turn(180 + spread)]
+            }
+            Harmony::Rectangle { spread } => {
+                let spread = spread as i32;
+                vec![turn(spread), 180, turn(180 + spread)]
+            }
+            Harmony::DoubleSplitComplementary { spread } => {
+                let spread = spread as i32;
+                vec![
+                    turn(-spread),
+                    turn(spread),
+                    turn(180 - spread),
+                    turn(180 + spread),
+                ]
+            }
+            Harmony::Compound { spread } => {
+                let spread = spread as i32;
+                vec![turn(spread), 180, turn(180 - spread)]
+            }
+            Harmony::AccentedAnalogous { spread } => {
+                let spread = spread as i32;
+                vec![turn(-spread), turn(spread), 180]
+            }
+        }
+    }
 }
 
-pub fn analogous(hsl: Hsl) -> (Hsl, Hsl) {
-    let left = (hsl.h as i32 - 30).rem_euclid(360);
-    let right = (hsl.h + 30).rem_euclid(360);
+impl fmt::Display for Harmony {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
 
-    let left = Hsl::new(left as u16, hsl.s, hsl.l);
-    let right = Hsl::new(right, hsl.s, hsl.l);
+/// The partners `rule` gives for `color`, with hues turned in `space`.
+pub fn harmony(
+    color: &Color,
+    rule: Harmony,
+    space: WorkingSpace,
+) -> Result<Vec<Color>, PaletteError> {
+    rule.offsets()
+        .into_iter()
+        .map(|offset| turn_hue(color, offset, 0, space))
+        .collect()
+}
 
-    (left, right)
+/// `color` turned `degrees` round the hue circle of `space` and made lighter or
+/// darker by `lightness` (x1000), keeping its saturation and alpha.
+pub fn turn_hue(
+    color: &Color,
+    degrees: u16,
+    lightness: i32,
+    space: WorkingSpace,
+) -> Result<Color, PaletteError> {
+    let (h, s, l) = space.components(color)?;
+    let h = ((h as u32 + degrees as u32) % 360) as u16;
+    let l = (l as i32 + lightness).clamp(0, 1000) as u16;
+    Color::new(space.encode(h, s, l).with_alpha(color.alpha()))
 }
 
-pub fn monochromatic(hsl: &Hsl) -> Result<Vec<Color>, PaletteError> {
+pub fn monochromatic(color: &Color, space: WorkingSpace) -> Result<Vec<Color>, PaletteError> {
     let mut monochrome: Vec<Color> = Vec::new();
+    let (h, s, l) = space.components(color)?;
 
-    for l in (50..hsl.l).step_by(50) {
-        monochrome.push(Color::new(Hsl::new(hsl.h, hsl.s, l).encode())?);
+    for step in (50..l).step_by(50) {
+        monochrome.push(Color::new(
+            space.encode(h, s, step).with_alpha(color.alpha()),
+        )?);
     }
 
-    for l in ((hsl.l + 50)..=1000).step_by(50) {
-        monochrome.push(Color::new(Hsl::new(hsl.h, hsl.s, l).encode())?);
+    for step in ((l + 50)..=1000).step_by(50) {
+        monochrome.push(Color::new(
+            space.encode(h, s, step).with_alpha(color.alpha()),
+        )?);
     }
 
     Ok(monochrome)
 }
 
-pub fn gradient(hsl: Hsl, hsl2: Hsl, num: u32) -> Vec<Hsl> {
-    assert!(hsl.h <= 360 && hsl2.h <= 360);
-    assert!(hsl.s <= 1000 && hsl2.s <= 1000);
-    assert!(hsl.l <= 1000 && hsl2.l <= 1000);
-    assert!(num > 1);
+pub fn gradient(hsl: Hsl, hsl2: Hsl, num: u32) -> Result<Vec<Hsl>, PaletteError> {
+    Hsl::try_new(hsl.h, hsl.s, hsl.l)?;
+    Hsl::try_new(hsl2.h, hsl2.s, hsl2.l)?;
+    if num < 2 {
+        return Err(PaletteError::InvalidFormat(
+            "a gradient needs at least 2 steps".to_string(),
+        ));
+    }
 
     let num = num as i32;
     let mut gradient: Vec<Hsl> = Vec::with_capacity(num as usize);
@@ -91,13 +312,13 @@ pub fn gradient(hsl: Hsl, hsl2: Hsl, num: u32) -> Vec<Hsl> {
 
     for _ in 0..num {
         h = (h + h_interval).rem_euclid(360);
-        s = s + s_interval;
-        l = l + l_interval;
+        s += s_interval;
+        l += l_interval;
 
         gradient.push(Hsl::new(h as u16, s as u16, l as u16));
     }
 
-    gradient
+    Ok(gradient)
 }
 
 pub fn three_node_distance_rgb(rgb1: Rgb, rgb2: Rgb) -> u32 {
@@ -111,155 +332,828 @@ pub fn three_node_distance_rgb(rgb1: Rgb, rgb2: Rgb) -> u32 {
     distance as u32
 }
 
-pub fn n_color_average_complement(nodes: &Vec<Color>) -> Result<Color, PaletteError> {
-    let mut complements: Vec<Hsl> = Vec::new();
+/// The space two colors are interpolated in by [`mix`].
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum MixSpace {
+    #[default]
+    LinearRgb,
+    Oklab,
+    Oklch(HuePath),
+    Hsl(HuePath),
+}
+
+/// Which way around the hue wheel to go, as in CSS Color 4.
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy, Default)]
+pub enum HuePath {
+    #[default]
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/// Interpolates from `color1` at t = 0 to `color2` at t = 1, t is clamped to that range.
+/// Alpha is always interpolated linearly.
+pub fn mix(color1: &Color, color2: &Color, t: f32, space: MixSpace) -> Result<Color, PaletteError> {
+    let t = t.clamp(0.0, 1.0);
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    let alpha = lerp(color1.alpha() as f32, color2.alpha() as f32).round() as u8;
+
+    let encoding = match space {
+        // already the canonical form, so skip the round trip through an encoding
+        MixSpace::LinearRgb => {
+            let ((r1, g1, b1), (r2, g2, b2)) = (color1.linear_srgb(), color2.linear_srgb());
+            return Ok(Color::from_linear_srgb(
+                (lerp(r1, r2), lerp(g1, g2), lerp(b1, b2)),
+                alpha,
+            ));
+        }
+        MixSpace::Oklab => {
+            let (ok1, ok2) = (color1.oklab(), color2.oklab());
+            Oklab::new(
+                lerp(ok1.l as f32, ok2.l as f32).round() as u16,
+                lerp(ok1.a as f32, ok2.a as f32).round() as i16,
+                lerp(ok1.b as f32, ok2.b as f32).round() as i16,
+            )
+            .encode()
+        }
+        MixSpace::Oklch(path) => {
+            let (lch1, lch2) = (color1.oklch(), color2.oklch());
+            let (h1, h2) = powerless_hues(lch1.h, lch1.c == 0, lch2.h, lch2.c == 0);
+            Oklch::new(
+                lerp(lch1.l as f32, lch2.l as f32).round() as u16,
+                lerp(lch1.c as f32, lch2.c as f32).round() as u16,
+                mix_hue(h1, h2, t, path),
+            )
+            .encode()
+        }
+        MixSpace::Hsl(path) => {
+            let (hsl1, hsl2) = (color1.hsl(), color2.hsl());
+            let (h1, h2) = powerless_hues(hsl1.h, hsl1.s == 0, hsl2.h, hsl2.s == 0);
+            Hsl::new(
+                mix_hue(h1, h2, t, path),
+                lerp(hsl1.s as f32, hsl2.s as f32).round() as u16,
+                lerp(hsl1.l as f32, hsl2.l as f32).round() as u16,
+            )
+            .encode()
+        }
+    };
+
+    Color::new(encoding.with_alpha(alpha))
+}
+
+// a gray has no meaningful hue, borrow the other color's so the mix doesn't swing through red
+fn powerless_hues(h1: u16, gray1: bool, h2: u16, gray2: bool) -> (f32, f32) {
+    match (gray1, gray2) {
+        (true, false) => (h2 as f32, h2 as f32),
+        (false, true) => (h1 as f32, h1 as f32),
+        _ => (h1 as f32, h2 as f32),
+    }
+}
+
+fn mix_hue(h1: f32, h2: f32, t: f32, path: HuePath) -> u16 {
+    let mut difference = h2 - h1;
+    match path {
+        HuePath::Shorter if difference > 180.0 => difference -= 360.0,
+        HuePath::Shorter if difference < -180.0 => difference += 360.0,
+        HuePath::Longer if difference > 0.0 && difference < 180.0 => difference -= 360.0,
+        HuePath::Longer if difference > -180.0 && difference <= 0.0 => difference += 360.0,
+        HuePath::Increasing if difference < 0.0 => difference += 360.0,
+        HuePath::Decreasing if difference > 0.0 => difference -= 360.0,
+        _ => {}
+    }
+    ((h1 + difference * t).rem_euclid(360.0).round() as u16) % 360
+}
+
+/// `color` mixed toward white by `amount`, from 0 to 1.
+pub fn tint(color: &Color, amount: f32) -> Result<Color, PaletteError> {
+    let white = Color::new(Oklab::new(1000, 0, 0).encode().with_alpha(color.alpha()))?;
+    mix(color, &white, amount, MixSpace::Oklab)
+}
+
+/// `color` mixed toward black by `amount`, from 0 to 1.
+pub fn shade(color: &Color, amount: f32) -> Result<Color, PaletteError> {
+    let black = Color::new(Oklab::new(0, 0, 0).encode().with_alpha(color.alpha()))?;
+    mix(color, &black, amount, MixSpace::Oklab)
+}
+
+/// `color` mixed toward gray by `amount`, from 0 to 1. The gray is as light as the
+/// color, so toning takes chroma away without changing lightness.
+pub fn tone(color: &Color, amount: f32) -> Result<Color, PaletteError> {
+    let gray = Color::new(
+        Oklab::new(color.oklab().l, 0, 0)
+            .encode()
+            .with_alpha(color.alpha()),
+    )?;
+    mix(color, &gray, amount, MixSpace::Oklab)
+}
+
+/// Tints, shades and tones of `color` as a grid. Rows go from the lightest tint
+/// through `color` in the middle row to the darkest shade, `steps` either side.
+/// Columns tone each row further toward gray, `steps` past the untoned first one.
+/// Neither axis reaches pure white, black or gray.
+pub fn variations(color: &Color, steps: usize) -> Result<Vec<Vec<Color>>, PaletteError> {
+    let amount = |step: usize| step as f32 / (steps + 1) as f32;
+    let mut rows = Vec::with_capacity(2 * steps + 1);
+    for step in (1..=steps).rev() {
+        rows.push(tint(color, amount(step))?);
+    }
+    rows.push(color.clone());
+    for step in 1..=steps {
+        rows.push(shade(color, amount(step))?);
+    }
+
+    rows.iter()
+        .map(|row| {
+            (0..=steps)
+                .map(|step| match step {
+                    0 => Ok(row.clone()),
+                    _ => tone(row, amount(step)),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The steps of a design system shade ladder, lightest first.
+pub const TONAL_STEPS: [u16; 11] = [50, 100, 200, 300, 400, 500, 600, 700, 800, 900, 950];
+
+// oklch lightness (x1000) of the 50 and 950 steps, the rest are spaced evenly between
+const TONAL_LIGHTEST: u16 = 970;
+const TONAL_DARKEST: u16 = 250;
+
+/// An 11 step scale of `color` at evenly spaced OKLCH lightness, keeping its hue and
+/// the share of the sRGB gamut its chroma fills. `color` itself sits at the step
+/// nearest its lightness.
+pub fn tonal_scale(color: &Color) -> Result<Vec<(u16, Color)>, PaletteError> {
+    let last = TONAL_STEPS.len() as u16 - 1;
+    let lightness = |i: u16| TONAL_LIGHTEST - (TONAL_LIGHTEST - TONAL_DARKEST) * i / last;
+    let (h, s, l) = WorkingSpace::Oklch.components(color)?;
+    let anchor = (0..=last)
+        .min_by_key(|&i| lightness(i).abs_diff(l))
+        .unwrap_or(0);
+
+    TONAL_STEPS
+        .iter()
+        .zip(0..)
+        .map(|(&step, i)| {
+            let color = if i == anchor {
+                color.clone()
+            } else {
+                Color::new(
+                    WorkingSpace::Oklch
+                        .encode(h, s, lightness(i))
+                        .with_alpha(color.alpha()),
+                )?
+            };
+            Ok((step, color))
+        })
+        .collect()
+}
+
+pub fn n_color_average_complement(
+    nodes: &Vec<Color>,
+    space: WorkingSpace,
+) -> Result<Color, PaletteError> {
+    let mut complements: Vec<Color> = Vec::with_capacity(nodes.len());
     for node in nodes {
-        complements.push(complement(&node.hsl));
+        complements.extend(harmony(node, Harmony::Complementary, space)?);
     }
-    let rgb = match complements.pop() {
-        Some(val) => val.encode().get_rgb()?,
-        None => {
-            return Err(PaletteError::InvalidFormat(
-                "no colors to complement".to_string(),
-            ));
+    let Some((first, rest)) = complements.split_first() else {
+        return Err(PaletteError::InvalidFormat(
+            "no colors to complement".to_string(),
+        ));
+    };
+
+    // running mean, the nth color gets a weight of 1/n
+    let mut average = first.clone();
+    for (i, complement) in rest.iter().enumerate() {
+        average = mix(
+            &average,
+            complement,
+            1.0 / (i + 2) as f32,
+            MixSpace::LinearRgb,
+        )?;
+    }
+
+    Ok(average)
+}
+
+/// How well a pair of colors passes WCAG 2.1 as text and background.
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum WcagLevel {
+    Fail,
+    /// Enough for large text, 18pt or 14pt bold, at AA.
+    AaLarge,
+    Aa,
+    Aaa,
+}
+
+impl WcagLevel {
+    /// The level normal sized text reaches at a contrast `ratio`.
+    pub fn from_ratio(ratio: f32) -> WcagLevel {
+        match ratio {
+            r if r >= 7.0 => WcagLevel::Aaa,
+            r if r >= 4.5 => WcagLevel::Aa,
+            r if r >= 3.0 => WcagLevel::AaLarge,
+            _ => WcagLevel::Fail,
+        }
+    }
+}
+
+impl fmt::Display for WcagLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WcagLevel::Fail => write!(f, "fail"),
+            WcagLevel::AaLarge => write!(f, "AA large"),
+            WcagLevel::Aa => write!(f, "AA"),
+            WcagLevel::Aaa => write!(f, "AAA"),
+        }
+    }
+}
+
+/// WCAG 2.1 relative luminance of the displayed color, from 0 for black to 1 for
+/// white. Alpha is ignored.
+pub fn relative_luminance(color: &Color) -> f32 {
+    let rgb = color.rgb();
+    0.2126 * srgb8_to_linear(rgb.r)
+        + 0.7152 * srgb8_to_linear(rgb.g)
+        + 0.0722 * srgb8_to_linear(rgb.b)
+}
+
+/// WCAG 2.1 contrast ratio, from 1 for the same luminance to 21 for black and white.
+/// The order of the colors doesn't matter.
+pub fn contrast_ratio(color1: &Color, color2: &Color) -> f32 {
+    let (l1, l2) = (relative_luminance(color1), relative_luminance(color2));
+    (l1.max(l2) + 0.05) / (l1.min(l2) + 0.05)
+}
+
+/// What text a pair of colors is good for by APCA, judged on the size of Lc so light
+/// text on dark passes the same way.
+#[derive(Hash, PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy)]
+pub enum ApcaLevel {
+    Fail,
+    /// Large or heavy text, headlines, Lc 45.
+    Large,
+    /// Text that isn't body copy, like labels, Lc 60.
+    Content,
+    /// Body text, Lc 75.
+    Body,
+}
+
+impl ApcaLevel {
+    pub fn from_lc(lc: f32) -> ApcaLevel {
+        match lc.abs() {
+            lc if lc >= 75.0 => ApcaLevel::Body,
+            lc if lc >= 60.0 => ApcaLevel::Content,
+            lc if lc >= 45.0 => ApcaLevel::Large,
+            _ => ApcaLevel::Fail,
+        }
+    }
+}
+
+impl fmt::Display for ApcaLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApcaLevel::Fail => write!(f, "fail"),
+            ApcaLevel::Large => write!(f, "large"),
+            ApcaLevel::Content => write!(f, "content"),
+            ApcaLevel::Body => write!(f, "body"),
+        }
+    }
+}
+
+// APCA-W3 0.0.98G-4g constants
+const APCA_BLACK_THRESHOLD: f32 = 0.022;
+const APCA_BLACK_CLAMP: f32 = 1.414;
+const APCA_SCALE: f32 = 1.14;
+const APCA_OFFSET: f32 = 0.027;
+const APCA_LOW_CLIP: f32 = 0.1;
+const APCA_MIN_DELTA_Y: f32 = 0.0005;
+
+/// APCA lightness contrast (Lc) of `text` on `background`, about 106 for black on
+/// white and -108 for white on black. Negative means light text on a dark background.
+/// Alpha is ignored.
+pub fn apca_contrast(text: &Color, background: &Color) -> f32 {
+    // apca's own luminance, a plain 2.4 power rather than the srgb curve
+    let luminance = |color: &Color| {
+        let rgb = color.rgb();
+        let channel = |c: u8| (c as f32 / 255.0).powf(2.4);
+        let y = 0.212_672_9 * channel(rgb.r)
+            + 0.715_152_2 * channel(rgb.g)
+            + 0.072_175 * channel(rgb.b);
+        // soft clamp near black, where screens flare
+        if y < APCA_BLACK_THRESHOLD {
+            y + (APCA_BLACK_THRESHOLD - y).powf(APCA_BLACK_CLAMP)
+        } else {
+            y
         }
     };
-    let mut r = rgb.r as u32;
-    let mut g = rgb.g as u32;
-    let mut b = rgb.b as u32;
-    for complement in complements {
-        let rgb = complement.encode().get_rgb()?;
-        r = (r + rgb.r as u32) / 2;
-        g = (r + rgb.g as u32) / 2;
-        b = (r + rgb.b as u32) / 2;
+    let (text, background) = (luminance(text), luminance(background));
+    if (background - text).abs() < APCA_MIN_DELTA_Y {
+        return 0.0;
     }
 
-    Ok(Color::new(Rgb::new(r as u8, g as u8, b as u8).encode())?)
+    let lc = if background > text {
+        let sapc = (background.powf(0.56) - text.powf(0.57)) * APCA_SCALE;
+        if sapc < APCA_LOW_CLIP {
+            0.0
+        } else {
+            sapc - APCA_OFFSET
+        }
+    } else {
+        let sapc = (background.powf(0.65) - text.powf(0.62)) * APCA_SCALE;
+        if sapc > -APCA_LOW_CLIP {
+            0.0
+        } else {
+            sapc + APCA_OFFSET
+        }
+    };
+    lc * 100.0
+}
+
+/// Black or white, whichever reads better on `background` by APCA.
+pub fn readable_text(background: &Color) -> Rgb {
+    let (black, white) = (Rgb::new(0, 0, 0), Rgb::new(255, 255, 255));
+    let on_background = |text: &Rgb| apca_contrast(&Color::from(text.clone()), background).abs();
+    if on_background(&white) > on_background(&black) {
+        white
+    } else {
+        black
+    }
 }
 
-pub fn generate_color() -> Result<Color, PaletteError> {
-    let mut rng = rand::rng();
+// planckian white closest to d65, a shift of zero leaves colors untouched
+const NEUTRAL_KELVIN: f32 = 6504.0;
+
+/// Warms (positive) or cools (negative) every unlocked color as if the light were shifted
+/// by `mired_shift` micro reciprocal degrees. Locked colors are returned as they are.
+/// Shifts past either end of the temperature range stop there.
+pub fn white_balance(colors: &[Color], mired_shift: f32) -> Result<Vec<Color>, PaletteError> {
+    // in mireds, so a large cool shift can't pass zero and come back warm
+    let mireds = (1_000_000.0 / NEUTRAL_KELVIN + mired_shift)
+        .clamp(1_000_000.0 / KELVIN_MAX, 1_000_000.0 / KELVIN_MIN);
+    let target = 1_000_000.0 / mireds;
+    let mut balanced = Vec::with_capacity(colors.len());
+    for color in colors {
+        if color.locked || mired_shift == 0.0 {
+            balanced.push(color.clone());
+            continue;
+        }
+        let adapted = color
+            .rgb()
+            .encode()
+            .adapt_between_kelvin(NEUTRAL_KELVIN, target)?;
+        balanced.push(Color::new(adapted)?);
+    }
+    Ok(balanced)
+}
+
+/// Where generated colors come from. Seeded, so the same seed and size give back
+/// the same palette.
+#[derive(Debug, Clone)]
+pub struct PaletteRng(StdRng);
+
+impl PaletteRng {
+    pub fn seeded(seed: u64) -> PaletteRng {
+        PaletteRng(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl Default for PaletteRng {
+    fn default() -> PaletteRng {
+        PaletteRng(StdRng::from_os_rng())
+    }
+}
+
+impl RngCore for PaletteRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.fill_bytes(dst)
+    }
+}
+
+pub fn generate_color(space: WorkingSpace, rng: &mut impl Rng) -> Result<Color, PaletteError> {
     let h = rng.random_range(0..361);
     let s = rng.random_range(0..1001);
     let l = rng.random_range(0..1001);
-    Ok(Color::new(Hsl::new(h, s, l).encode())?)
-}
-
-pub fn generate_palette(num: usize) -> Result<Vec<Color>, PaletteError> {
-    assert!(num > 0);
-    let mut new_palette = Vec::with_capacity(num as usize);
-    new_palette.push(generate_color()?);
-    let mut i = 1;
-    if i < num {
-        new_palette.push(Color::new(complement(&new_palette[0].hsl).encode())?);
-        i += 1;
-    }
-    let mut rng = rand::rng();
-    while i < num {
-        let method = if num - i < 2 {
-            rng.random_range(0..3)
-        } else if num - i < 3 {
-            rng.random_range(0..4)
-        } else {
-            rng.random_range(0..5)
+    Color::new(space.encode(h, s, l))
+}
+
+/// A whole new palette from the random harmony strategy, empty when `num` is 0.
+pub fn generate_palette(
+    num: usize,
+    space: WorkingSpace,
+    rng: &mut impl Rng,
+) -> Result<Vec<Color>, PaletteError> {
+    generate_distinct(&RandomHarmony, &[], num, space, rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    const SPACES: [WorkingSpace; 4] = [
+        WorkingSpace::Hsl,
+        WorkingSpace::Hsluv,
+        WorkingSpace::Hpluv,
+        WorkingSpace::Oklch,
+    ];
+
+    #[test]
+    fn generate_palette_returns_right_length() {
+        for space in SPACES {
+            let palette = generate_palette(5, space, &mut PaletteRng::default()).unwrap();
+            assert_eq!(palette.len(), 5);
+            let empty = generate_palette(0, space, &mut PaletteRng::default()).unwrap();
+            assert!(empty.is_empty());
+        }
+    }
+
+    #[test]
+    fn same_seed_same_palette() {
+        let generate = |size, seed| {
+            generate_palette(size, WorkingSpace::Hsl, &mut PaletteRng::seeded(seed)).unwrap()
         };
-        let index = rng.random_range(0..new_palette.len());
-        match method {
-            0 => {
-                new_palette.push(Color::new(complement(&new_palette[index].hsl).encode())?);
-            }
-            1 => {
-                new_palette.push(generate_color()?);
-            }
-            2 => {
-                let new_color = n_color_average_complement(&new_palette)?;
-                new_palette.push(new_color);
-            }
-            3 => {
-                let (hsl1, hsl2) = triad(&new_palette[index].hsl);
-                new_palette.push(Color::new(hsl1.encode())?);
-                new_palette.push(Color::new(hsl2.encode())?);
-                i += 1;
-            }
-            4 => {
-                let (hsl1, hsl2, hsl3) = square(&new_palette[index].hsl);
-                new_palette.push(Color::new(hsl1.encode())?);
-                new_palette.push(Color::new(hsl2.encode())?);
-                new_palette.push(Color::new(hsl3.encode())?);
-                i += 2;
+        for size in 1..=8 {
+            println!("input: size {size}");
+            assert_eq!(generate(size, 12345), generate(size, 12345));
+            assert_ne!(generate(size, 12345), generate(size, 54321));
+        }
+    }
+
+    #[test]
+    fn oklch_saturation_is_a_share_of_the_gamut() {
+        for (h, s, l) in [
+            (207, 500, 600),
+            (30, 1000, 700),
+            (140, 250, 300),
+            (0, 0, 500),
+        ] {
+            println!("input: {:?}", (h, s, l));
+            let color = Color::new(WorkingSpace::Oklch.encode(h, s, l)).unwrap();
+            assert!(color.in_srgb_gamut());
+            let (found_h, found_s, found_l) = WorkingSpace::Oklch.components(&color).unwrap();
+            if s > 0 {
+                assert!(found_h.abs_diff(h) <= 2, "{found_h}");
             }
-            _ => {
-                return Err(PaletteError::UntranslatableEncoding(
-                    "generate palette from base generated an invalid number".to_string(),
-                ));
+            assert!(found_s.abs_diff(s) <= 20, "{found_s}");
+            assert!(found_l.abs_diff(l) <= 2, "{found_l}");
+        }
+    }
+
+    #[test]
+    fn harmonies_turn_hue_in_the_working_space() {
+        let color = Color::new(Rgb::new(70, 130, 180).encode()).unwrap();
+        for space in SPACES {
+            let (h, _, _) = space.components(&color).unwrap();
+            for rule in Harmony::ALL {
+                println!("input: {space} {rule}");
+                let partners = harmony(&color, rule, space).unwrap();
+                assert_eq!(partners.len(), rule.offsets().len());
+                for (partner, offset) in partners.iter().zip(rule.offsets()) {
+                    let (found, _, _) = space.components(partner).unwrap();
+                    let wanted = (h + offset) % 360;
+                    let off_by = found.abs_diff(wanted).min(360 - found.abs_diff(wanted));
+                    assert!(off_by <= 3, "{found} vs {wanted}");
+                }
             }
         }
-        i += 1;
     }
-    Ok(new_palette)
-}
 
-pub fn generate_palette_from_base(
-    current_palette: &Vec<Color>,
-    num: usize,
-) -> Result<Vec<Color>, PaletteError> {
-    let mut rng = rand::rng();
-    let mut temp_palette = Vec::with_capacity(num as usize);
-    let mut i = 1;
-    while i < num {
-        let method = if num - i < 2 {
-            rng.random_range(0..3)
-        } else if num - i < 3 {
-            rng.random_range(0..4)
-        } else {
-            rng.random_range(0..5)
-        };
-        let index = rng.random_range(0..current_palette.len());
-        match method {
-            0 => {
-                temp_palette.push(Color::new(
-                    complement(&current_palette[index].hsl).encode(),
-                )?);
+    #[test]
+    fn harmony_spreads_are_configurable() {
+        let tests: HashMap<Harmony, Vec<u16>> = HashMap::from([
+            (Harmony::Complementary, vec![180]),
+            (Harmony::Analogous { spread: 20 }, vec![340, 20]),
+            (Harmony::SplitComplementary { spread: 15 }, vec![165, 195]),
+            (Harmony::Rectangle { spread: 30 }, vec![30, 180, 210]),
+            (
+                Harmony::DoubleSplitComplementary { spread: 45 },
+                vec![315, 45, 135, 225],
+            ),
+            (Harmony::Compound { spread: 30 }, vec![30, 180, 150]),
+            (
+                Harmony::AccentedAnalogous { spread: 30 },
+                vec![330, 30, 180],
+            ),
+        ]);
+
+        for (rule, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", rule, expected);
+            assert_eq!(rule.offsets(), expected);
+        }
+        assert_eq!(
+            Harmony::Rectangle { spread: 10 }.next(),
+            Harmony::DoubleSplitComplementary { spread: 30 }
+        );
+    }
+
+    #[test]
+    fn monochromatic_steps_lightness_in_the_working_space() {
+        let color = Color::new(Rgb::new(70, 130, 180).encode().with_alpha(200)).unwrap();
+        for space in SPACES {
+            let (h, _, _) = space.components(&color).unwrap();
+            let scale = monochromatic(&color, space).unwrap();
+            println!("input: {:?}, scale length: {}", space, scale.len());
+            assert!(scale.len() >= 18);
+            for step in &scale {
+                assert_eq!(step.alpha(), 200);
+                let (step_h, step_s, step_l) = space.components(step).unwrap();
+                // hue only means something away from black and white, and hpluv steps
+                // past the srgb gamut drift a little when they are mapped back in
+                if step_s > 100 && (100..=900).contains(&step_l) {
+                    assert!(
+                        (step_h as i32 - h as i32).abs() <= 5,
+                        "{space}: {step_h} vs {h} at {step_l}"
+                    );
+                }
             }
-            1 => {
-                temp_palette.push(generate_color()?);
+        }
+    }
+
+    #[test]
+    fn test_mix() {
+        let black = Color::new(Rgb::new(0, 0, 0).encode()).unwrap();
+        let white = Color::new(Rgb::new(255, 255, 255).encode()).unwrap();
+        let red = Color::new(Rgb::new(255, 0, 0).encode()).unwrap();
+        let blue = Color::new(Rgb::new(0, 0, 255).encode()).unwrap();
+
+        let tests: HashMap<MixSpace, (&Color, &Color, Rgb)> = HashMap::from([
+            // half the light, not half the byte value
+            (
+                MixSpace::LinearRgb,
+                (&black, &white, Rgb::new(188, 188, 188)),
+            ),
+            (
+                MixSpace::Hsl(HuePath::Shorter),
+                (&black, &white, Rgb::new(128, 128, 128)),
+            ),
+            (MixSpace::Oklab, (&black, &white, Rgb::new(99, 99, 99))),
+            (
+                MixSpace::Hsl(HuePath::Longer),
+                (&red, &blue, Rgb::new(0, 255, 0)),
+            ),
+            (
+                MixSpace::Hsl(HuePath::Decreasing),
+                (&red, &blue, Rgb::new(255, 0, 255)),
+            ),
+            (
+                MixSpace::Oklch(HuePath::Increasing),
+                (&red, &white, Rgb::new(255, 161, 146)),
+            ),
+        ]);
+
+        for (space, (color1, color2, expected)) in tests {
+            println!("input: {:?}, desired result: {:?}", space, expected);
+            match mix(color1, color2, 0.5, space) {
+                Ok(result) => assert_eq!(*result.rgb(), expected),
+                Err(e) => panic!("{e}"),
             }
-            2 => {
-                let new_color = n_color_average_complement(current_palette)?;
-                temp_palette.push(new_color);
+        }
+    }
+
+    #[test]
+    fn variations_run_light_to_dark_and_vivid_to_gray() {
+        let color = Color::new(Rgb::new(200, 60, 40).encode().with_alpha(180)).unwrap();
+        for steps in 1..=4 {
+            println!("input: {steps} steps");
+            let grid = variations(&color, steps).unwrap();
+            assert_eq!(grid.len(), 2 * steps + 1);
+            assert_eq!(grid[steps][0], color);
+
+            for row in &grid {
+                assert_eq!(row.len(), steps + 1);
+                for pair in row.windows(2) {
+                    assert!(pair[1].oklch().c < pair[0].oklch().c);
+                    assert!(pair[1].oklab().l.abs_diff(pair[0].oklab().l) <= 2);
+                }
+                assert!(row.iter().all(|cell| cell.alpha() == 180));
             }
-            3 => {
-                let (hsl1, hsl2) = triad(&current_palette[index].hsl);
-                temp_palette.push(Color::new(hsl1.encode())?);
-                temp_palette.push(Color::new(hsl2.encode())?);
-                i += 1;
+            for pair in grid.windows(2) {
+                assert!(pair[1][0].oklab().l < pair[0][0].oklab().l);
             }
-            4 => {
-                let (hsl1, hsl2, hsl3) = square(&current_palette[index].hsl);
-                temp_palette.push(Color::new(hsl1.encode())?);
-                temp_palette.push(Color::new(hsl2.encode())?);
-                temp_palette.push(Color::new(hsl3.encode())?);
-                i += 2;
+        }
+
+        // no step reaches the end it mixes toward
+        let grid = variations(&color, 2).unwrap();
+        assert!(grid[0][0].oklab().l < 1000 && grid[4][0].oklab().l > 0);
+        assert!(grid[2][2].oklch().c > 0);
+    }
+
+    #[test]
+    fn tonal_scale_is_even_and_anchored() {
+        for rgb in [
+            Rgb::new(205, 92, 92),
+            Rgb::new(59, 130, 246),
+            Rgb::new(250, 204, 21),
+            Rgb::new(20, 20, 20),
+            Rgb::new(128, 128, 128),
+        ] {
+            println!("input: {:?}", rgb);
+            let color = Color::new(rgb.encode()).unwrap();
+            let scale = tonal_scale(&color).unwrap();
+            let steps: Vec<u16> = scale.iter().map(|(step, _)| *step).collect();
+            assert_eq!(steps, TONAL_STEPS);
+            assert_eq!(scale.iter().filter(|(_, step)| *step == color).count(), 1);
+
+            for pair in scale.windows(2) {
+                assert!(pair[1].1.oklab().l < pair[0].1.oklab().l);
             }
-            _ => {
-                return Err(PaletteError::InvalidFormat(
-                    "generate palette from base generated an invalid random number".to_string(),
-                ));
+            for (_, step) in &scale {
+                assert!(step.in_srgb_gamut());
+                if step.oklch().c > 20 && color.oklch().c > 20 {
+                    let off_by = step.oklch().h.abs_diff(color.oklch().h);
+                    assert!(off_by.min(360 - off_by) <= 3, "{:?}", step.oklch());
+                }
             }
         }
-        i += 1;
     }
 
-    Ok(temp_palette)
-}
+    #[test]
+    fn mix_endpoints_are_the_inputs() {
+        let red = Color::new(Rgb::new(255, 0, 0).encode()).unwrap();
+        let teal = Color::new(Rgb::new(0, 128, 128).encode().with_alpha(64)).unwrap();
+        for space in [
+            MixSpace::LinearRgb,
+            MixSpace::Oklab,
+            MixSpace::Oklch(HuePath::Shorter),
+            MixSpace::Hsl(HuePath::Shorter),
+        ] {
+            // the perceptual spaces are stored quantized, so allow a little drift
+            let start = mix(&red, &teal, 0.0, space).unwrap().rgb().clone();
+            println!("input: {:?}, start: {:?}", space, start);
+            assert!(three_node_distance_rgb(start, red.rgb().clone()) <= 16);
+            assert_eq!(mix(&red, &teal, 1.0, space).unwrap().alpha(), 64);
+        }
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn average_complement_uses_every_channel() {
+        // complements are cyan, magenta and yellow
+        let nodes = vec![
+            Color::new(Rgb::new(255, 0, 0).encode()).unwrap(),
+            Color::new(Rgb::new(0, 255, 0).encode()).unwrap(),
+            Color::new(Rgb::new(0, 0, 255).encode()).unwrap(),
+        ];
+        let average = n_color_average_complement(&nodes, WorkingSpace::Hsl).unwrap();
+        // two thirds of full linear light in every channel
+        assert_eq!(*average.rgb(), Rgb::new(213, 213, 213));
+    }
 
     #[test]
-    fn generate_palette_returns_right_length() {
-        let palette = generate_palette(5).unwrap();
-        assert_eq!(palette.len(), 5);
+    fn white_balance_warms_and_cools() {
+        let mut colors = vec![
+            Color::new(Rgb::new(255, 255, 255).encode()).unwrap(),
+            Color::new(Rgb::new(128, 128, 128).encode().with_alpha(128)).unwrap(),
+            Color::new(Rgb::new(70, 130, 180).encode()).unwrap(),
+        ];
+        colors[2].locked = true;
+
+        let warmer = white_balance(&colors, 50.0).unwrap();
+        assert!(warmer[0].rgb().r > warmer[0].rgb().b);
+        assert!(warmer[1].rgb().r > warmer[1].rgb().b);
+        assert_eq!(warmer[1].alpha(), 128);
+        assert_eq!(warmer[2], colors[2]);
+        assert!(warmer[0].correlated_color_temperature().unwrap().k < 6000);
+
+        let cooler = white_balance(&colors, -50.0).unwrap();
+        assert!(cooler[0].rgb().b > cooler[0].rgb().r);
+        assert!(cooler[0].correlated_color_temperature().unwrap().k > 7000);
+
+        assert_eq!(white_balance(&colors, 0.0).unwrap(), colors);
+
+        // past the ends of the temperature range, never flipping to the other side
+        for shift in [-153.75, -200.0, -1000.0] {
+            println!("input: {shift}, desired result: cooler");
+            let coolest = white_balance(&colors, shift).unwrap();
+            assert!(coolest[0].rgb().b > coolest[0].rgb().r);
+            assert!(coolest[0].correlated_color_temperature().unwrap().k > 10000);
+        }
+        assert_eq!(
+            white_balance(&colors, -200.0).unwrap(),
+            white_balance(&colors, -1000.0).unwrap()
+        );
+        let warmest = white_balance(&colors, 1000.0).unwrap();
+        assert!(warmest[0].rgb().r > warmest[0].rgb().b);
+        assert!(warmest[0].correlated_color_temperature().unwrap().k < 2500);
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let tests: HashMap<(Encoding, Encoding), (f32, WcagLevel)> = HashMap::from([
+            (
+                (Encoding::Rgb(0, 0, 0), Encoding::Rgb(255, 255, 255)),
+                (21.0, WcagLevel::Aaa),
+            ),
+            (
+                (Encoding::Rgb(255, 255, 255), Encoding::Rgb(0, 0, 0)),
+                (21.0, WcagLevel::Aaa),
+            ),
+            (
+                (Encoding::Rgb(119, 119, 119), Encoding::Rgb(255, 255, 255)),
+                (4.48, WcagLevel::AaLarge),
+            ),
+            (
+                (Encoding::Rgb(118, 118, 118), Encoding::Rgb(255, 255, 255)),
+                (4.54, WcagLevel::Aa),
+            ),
+            (
+                (Encoding::Rgb(255, 0, 0), Encoding::Rgb(255, 255, 255)),
+                (4.0, WcagLevel::AaLarge),
+            ),
+            (
+                (Encoding::Rgb(70, 130, 180), Encoding::Rgb(70, 130, 180)),
+                (1.0, WcagLevel::Fail),
+            ),
+        ]);
+
+        for ((text, background), (ratio, level)) in tests {
+            println!("input: {text:?} on {background:?}, desired result: {ratio} {level}");
+            let text = Color::new(text).unwrap();
+            let background = Color::new(background).unwrap();
+            let found = contrast_ratio(&text, &background);
+            assert!((found - ratio).abs() < 0.01, "{found}");
+            assert_eq!(WcagLevel::from_ratio(found), level);
+        }
+    }
+
+    #[test]
+    fn test_apca_contrast() {
+        // values from the apca-w3 reference implementation
+        let tests: HashMap<(Encoding, Encoding), f32> = HashMap::from([
+            (
+                (Encoding::Rgb(0, 0, 0), Encoding::Rgb(255, 255, 255)),
+                106.04,
+            ),
+            (
+                (Encoding::Rgb(255, 255, 255), Encoding::Rgb(0, 0, 0)),
+                -107.88,
+            ),
+            (
+                (Encoding::Rgb(136, 136, 136), Encoding::Rgb(255, 255, 255)),
+                63.06,
+            ),
+            (
+                (Encoding::Rgb(255, 255, 255), Encoding::Rgb(136, 136, 136)),
+                -68.54,
+            ),
+            (
+                (Encoding::Rgb(17, 34, 51), Encoding::Rgb(221, 238, 255)),
+                91.67,
+            ),
+            (
+                (Encoding::Rgb(100, 100, 100), Encoding::Rgb(100, 100, 100)),
+                0.0,
+            ),
+        ]);
+
+        for ((text, background), expected) in tests {
+            println!("input: {text:?} on {background:?}, desired result: {expected}");
+            let text = Color::new(text).unwrap();
+            let background = Color::new(background).unwrap();
+            let found = apca_contrast(&text, &background);
+            assert!((found - expected).abs() < 0.05, "{found}");
+        }
+
+        let levels = [
+            (106.0, ApcaLevel::Body),
+            (-75.0, ApcaLevel::Body),
+            (63.1, ApcaLevel::Content),
+            (-68.5, ApcaLevel::Content),
+            (45.0, ApcaLevel::Large),
+            (44.9, ApcaLevel::Fail),
+            (0.0, ApcaLevel::Fail),
+        ];
+        for (lc, level) in levels {
+            println!("input: Lc {lc}, desired result: {level}");
+            assert_eq!(ApcaLevel::from_lc(lc), level);
+        }
+    }
+
+    #[test]
+    fn readable_text_is_black_or_white() {
+        let tests: HashMap<Encoding, Rgb> = HashMap::from([
+            (Encoding::Rgb(255, 255, 0), Rgb::new(0, 0, 0)),
+            (Encoding::Rgb(0, 0, 255), Rgb::new(255, 255, 255)),
+            (Encoding::Rgb(255, 255, 255), Rgb::new(0, 0, 0)),
+            (Encoding::Rgb(20, 20, 20), Rgb::new(255, 255, 255)),
+            // hsl calls this light, it still needs white text
+            (Encoding::Rgb(80, 80, 255), Rgb::new(255, 255, 255)),
+            (Encoding::Rgb(0, 200, 200), Rgb::new(0, 0, 0)),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {input:?}, desired result: {expected:?}");
+            assert_eq!(readable_text(&Color::new(input).unwrap()), expected);
+        }
     }
 }
@@ -1,234 +1,1063 @@
-use std::fmt;
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    sync::OnceLock,
+};
 
-use crate::{encoding::Encoding, error::PaletteError};
+use crate::{
+    color_distance,
+    color_math::readable_text,
+    describe::describe,
+    encoding::{
+        ColorSpace, Encoding, KELVIN_MAX, KELVIN_MIN, linear_in_srgb_gamut, srgb8_to_linear,
+    },
+    error::PaletteError,
+    named_colors,
+    terminal::ColorSupport,
+};
 
 // TODO
 
-#[derive(Hash, Eq, Debug, Clone)]
+pub const OPAQUE: u8 = 255;
+
+// per mille channels print as percentages, 531 is 53.1%
+fn percent(value: u16) -> f32 {
+    value as f32 / 10.0
+}
+
+// three decimals is enough for every 8 bit alpha to parse back to itself
+fn alpha_suffix(a: u8) -> String {
+    if a == OPAQUE {
+        return String::new();
+    }
+    format!(" / {}", (a as f32 / 255.0 * 1000.0).round() / 1000.0)
+}
+
+pub(crate) fn check_range(channel: &str, value: u32, max: u32) -> Result<(), PaletteError> {
+    if value > max {
+        return Err(PaletteError::OutOfRange {
+            channel: channel.to_string(),
+            value,
+            max,
+        });
+    }
+    Ok(())
+}
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub struct Hsl {
     pub h: u16,
     pub s: u16,
     pub l: u16,
-}
-
-impl PartialEq for Hsl {
-    fn eq(&self, other: &Hsl) -> bool {
-        self.h == other.h && self.s == other.s && self.l == other.l
-    }
+    pub a: u8,
 }
 
 impl Hsl {
     pub fn encode(&self) -> Encoding {
-        Encoding::Hsl(self.h, self.s, self.l)
+        Encoding::Hsl(self.h, self.s, self.l).with_alpha(self.a)
     }
 
     pub fn new(h: u16, s: u16, l: u16) -> Hsl {
-        Hsl { h: h, s: s, l: l }
+        Hsl { h, s, l, a: OPAQUE }
+    }
+
+    pub fn try_new(h: u16, s: u16, l: u16) -> Result<Hsl, PaletteError> {
+        check_range("hue", h as u32, 360)?;
+        check_range("saturation", s as u32, 1000)?;
+        check_range("lightness", l as u32, 1000)?;
+        Ok(Hsl::new(h, s, l))
+    }
+
+    pub fn with_alpha(self, a: u8) -> Hsl {
+        Hsl { a, ..self }
+    }
+}
+
+impl fmt::Display for Hsl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hsl({} {}% {}%{})",
+            self.h,
+            percent(self.s),
+            percent(self.l),
+            alpha_suffix(self.a)
+        )
     }
 }
 
 // -----------------------
 
-#[derive(Hash, Eq, Debug, Clone)]
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub struct Rgb {
     pub r: u8,
     pub g: u8,
     pub b: u8,
-}
-
-impl PartialEq for Rgb {
-    fn eq(&self, other: &Rgb) -> bool {
-        self.r == other.r && self.g == other.g && self.b == other.b
-    }
+    pub a: u8,
 }
 
 impl Rgb {
     pub fn encode(&self) -> Encoding {
-        Encoding::Rgb(self.r, self.g, self.b)
+        Encoding::Rgb(self.r, self.g, self.b).with_alpha(self.a)
     }
 
     pub fn new(r: u8, g: u8, b: u8) -> Rgb {
-        Rgb { r: r, g: g, b: b }
+        Rgb { r, g, b, a: OPAQUE }
+    }
+
+    pub fn with_alpha(self, a: u8) -> Rgb {
+        Rgb { a, ..self }
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rgb({} {} {}{})",
+            self.r,
+            self.g,
+            self.b,
+            alpha_suffix(self.a)
+        )
     }
 }
 
 // -----------------------
 
-#[derive(Hash, Eq, Debug, Clone)]
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub struct Hsb {
     pub h: u16,
     pub s: u16,
     pub b: u16,
-}
-
-impl PartialEq for Hsb {
-    fn eq(&self, other: &Hsb) -> bool {
-        self.h == other.h && self.s == other.s && self.b == other.b
-    }
+    pub a: u8,
 }
 
 impl Hsb {
     pub fn encode(&self) -> Encoding {
-        Encoding::Hsb(self.h, self.s, self.b)
+        Encoding::Hsb(self.h, self.s, self.b).with_alpha(self.a)
     }
 
     pub fn new(h: u16, s: u16, b: u16) -> Hsb {
-        Hsb { h: h, s: s, b: b }
+        Hsb { h, s, b, a: OPAQUE }
+    }
+
+    pub fn try_new(h: u16, s: u16, b: u16) -> Result<Hsb, PaletteError> {
+        check_range("hue", h as u32, 360)?;
+        check_range("saturation", s as u32, 1000)?;
+        check_range("brightness", b as u32, 1000)?;
+        Ok(Hsb::new(h, s, b))
+    }
+
+    pub fn with_alpha(self, a: u8) -> Hsb {
+        Hsb { a, ..self }
+    }
+}
+
+// not css, but the parser reads it back
+impl fmt::Display for Hsb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hsb({} {}% {}%{})",
+            self.h,
+            percent(self.s),
+            percent(self.b),
+            alpha_suffix(self.a)
+        )
     }
 }
 
 // -----------------------
 
-#[derive(Hash, Eq, Debug, Clone)]
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub struct Hex {
     pub h: u32,
+    pub a: u8,
 }
 
-impl PartialEq for Hex {
-    fn eq(&self, other: &Hex) -> bool {
-        self.h == other.h
+impl Hex {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Hex(self.h).with_alpha(self.a)
+    }
+
+    pub fn new(h: u32) -> Hex {
+        Hex { h, a: OPAQUE }
+    }
+
+    pub fn try_new(h: u32) -> Result<Hex, PaletteError> {
+        check_range("hex", h, 0xFFFFFF)?;
+        Ok(Hex::new(h))
+    }
+
+    pub fn with_alpha(self, a: u8) -> Hex {
+        Hex { a, ..self }
     }
 }
 
-impl Hex {
+impl fmt::Display for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.a == OPAQUE {
+            write!(f, "#{:06x}", self.h)
+        } else {
+            write!(f, "#{:06x}{:02x}", self.h, self.a)
+        }
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Oklab {
+    pub l: u16,
+    pub a: i16,
+    pub b: i16,
+}
+
+impl Oklab {
     pub fn encode(&self) -> Encoding {
-        Encoding::Hex(self.h)
+        Encoding::Oklab(self.l, self.a, self.b)
     }
 
-    pub fn new(h: u32) -> Hex {
-        Hex { h: h }
+    pub fn new(l: u16, a: i16, b: i16) -> Oklab {
+        Oklab { l, a, b }
+    }
+
+    /// a and b go 500 either side of 0, wider than rec2020 reaches.
+    pub fn try_new(l: u16, a: i16, b: i16) -> Result<Oklab, PaletteError> {
+        check_range("lightness", l as u32, 1000)?;
+        check_range("a", a.unsigned_abs() as u32, 500)?;
+        check_range("b", b.unsigned_abs() as u32, 500)?;
+        Ok(Oklab::new(l, a, b))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Oklch {
+    pub l: u16,
+    pub c: u16,
+    pub h: u16,
+}
+
+impl Oklch {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Oklch(self.l, self.c, self.h)
+    }
+
+    pub fn new(l: u16, c: u16, h: u16) -> Oklch {
+        Oklch { l, c, h }
+    }
+
+    pub fn try_new(l: u16, c: u16, h: u16) -> Result<Oklch, PaletteError> {
+        check_range("lightness", l as u32, 1000)?;
+        check_range("chroma", c as u32, 500)?;
+        check_range("hue", h as u32, 360)?;
+        Ok(Oklch::new(l, c, h))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum WhitePoint {
+    D50,
+    D65,
+}
+
+impl WhitePoint {
+    pub fn xyz(&self) -> (f32, f32, f32) {
+        match self {
+            WhitePoint::D50 => (0.964_22, 1.0, 0.825_21),
+            WhitePoint::D65 => (0.950_47, 1.0, 1.088_83),
+        }
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Xyz {
+    pub white: WhitePoint,
+    pub x: u16,
+    pub y: u16,
+    pub z: u16,
+}
+
+impl Xyz {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Xyz(self.white, self.x, self.y, self.z)
+    }
+
+    pub fn new(white: WhitePoint, x: u16, y: u16, z: u16) -> Xyz {
+        Xyz { white, x, y, z }
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Lab {
+    pub l: u16,
+    pub a: i16,
+    pub b: i16,
+}
+
+impl Lab {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Lab(self.l, self.a, self.b)
+    }
+
+    pub fn new(l: u16, a: i16, b: i16) -> Lab {
+        Lab { l, a, b }
+    }
+
+    /// a and b go 2000 either side of 0, wider than rec2020 reaches.
+    pub fn try_new(l: u16, a: i16, b: i16) -> Result<Lab, PaletteError> {
+        check_range("lightness", l as u32, 1000)?;
+        check_range("a", a.unsigned_abs() as u32, 2000)?;
+        check_range("b", b.unsigned_abs() as u32, 2000)?;
+        Ok(Lab::new(l, a, b))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Lch {
+    pub l: u16,
+    pub c: u16,
+    pub h: u16,
+}
+
+impl Lch {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Lch(self.l, self.c, self.h)
+    }
+
+    pub fn new(l: u16, c: u16, h: u16) -> Lch {
+        Lch { l, c, h }
+    }
+
+    pub fn try_new(l: u16, c: u16, h: u16) -> Result<Lch, PaletteError> {
+        check_range("lightness", l as u32, 1000)?;
+        check_range("chroma", c as u32, 2500)?;
+        check_range("hue", h as u32, 360)?;
+        Ok(Lch::new(l, c, h))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Cmyk {
+    pub c: u16,
+    pub m: u16,
+    pub y: u16,
+    pub k: u16,
+}
+
+impl Cmyk {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Cmyk(self.c, self.m, self.y, self.k)
+    }
+
+    pub fn new(c: u16, m: u16, y: u16, k: u16) -> Cmyk {
+        Cmyk { c, m, y, k }
+    }
+
+    pub fn try_new(c: u16, m: u16, y: u16, k: u16) -> Result<Cmyk, PaletteError> {
+        check_range("cyan", c as u32, 1000)?;
+        check_range("magenta", m as u32, 1000)?;
+        check_range("yellow", y as u32, 1000)?;
+        check_range("black", k as u32, 1000)?;
+        Ok(Cmyk::new(c, m, y, k))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct DisplayP3 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+
+impl DisplayP3 {
+    pub fn encode(&self) -> Encoding {
+        Encoding::DisplayP3(self.r, self.g, self.b)
+    }
+
+    pub fn new(r: u16, g: u16, b: u16) -> DisplayP3 {
+        DisplayP3 { r, g, b }
+    }
+
+    pub fn try_new(r: u16, g: u16, b: u16) -> Result<DisplayP3, PaletteError> {
+        check_range("red", r as u32, 1000)?;
+        check_range("green", g as u32, 1000)?;
+        check_range("blue", b as u32, 1000)?;
+        Ok(DisplayP3::new(r, g, b))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Rec2020 {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+}
+
+impl Rec2020 {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Rec2020(self.r, self.g, self.b)
+    }
+
+    pub fn new(r: u16, g: u16, b: u16) -> Rec2020 {
+        Rec2020 { r, g, b }
+    }
+
+    pub fn try_new(r: u16, g: u16, b: u16) -> Result<Rec2020, PaletteError> {
+        check_range("red", r as u32, 1000)?;
+        check_range("green", g as u32, 1000)?;
+        check_range("blue", b as u32, 1000)?;
+        Ok(Rec2020::new(r, g, b))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Kelvin {
+    pub k: u16,
+}
+
+impl Kelvin {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Kelvin(self.k)
+    }
+
+    pub fn new(k: u16) -> Kelvin {
+        Kelvin { k }
+    }
+
+    /// Between 1667 and 25000, where the approximation holds. Below is out of range
+    /// too, with the top as its most.
+    pub fn try_new(k: u16) -> Result<Kelvin, PaletteError> {
+        if (k as f32) < KELVIN_MIN {
+            return Err(PaletteError::OutOfRange {
+                channel: "temperature".to_string(),
+                value: k as u32,
+                max: KELVIN_MAX as u32,
+            });
+        }
+        check_range("temperature", k as u32, KELVIN_MAX as u32)?;
+        Ok(Kelvin::new(k))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Hsluv {
+    pub h: u16,
+    pub s: u16,
+    pub l: u16,
+}
+
+impl Hsluv {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Hsluv(self.h, self.s, self.l)
+    }
+
+    pub fn new(h: u16, s: u16, l: u16) -> Hsluv {
+        Hsluv { h, s, l }
+    }
+
+    pub fn try_new(h: u16, s: u16, l: u16) -> Result<Hsluv, PaletteError> {
+        check_range("hue", h as u32, 360)?;
+        check_range("saturation", s as u32, 1000)?;
+        check_range("lightness", l as u32, 1000)?;
+        Ok(Hsluv::new(h, s, l))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Hpluv {
+    pub h: u16,
+    pub s: u16,
+    pub l: u16,
+}
+
+impl Hpluv {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Hpluv(self.h, self.s, self.l)
+    }
+
+    pub fn new(h: u16, s: u16, l: u16) -> Hpluv {
+        Hpluv { h, s, l }
+    }
+
+    /// Saturation has no most, past 1000 is outside srgb and dark colors go far past.
+    pub fn try_new(h: u16, s: u16, l: u16) -> Result<Hpluv, PaletteError> {
+        check_range("hue", h as u32, 360)?;
+        check_range("lightness", l as u32, 1000)?;
+        Ok(Hpluv::new(h, s, l))
+    }
+}
+
+// -----------------------
+
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
+pub struct Ansi256 {
+    pub i: u8,
+}
+
+impl Ansi256 {
+    pub fn encode(&self) -> Encoding {
+        Encoding::Ansi256(self.i)
+    }
+
+    pub fn new(i: u8) -> Ansi256 {
+        Ansi256 { i }
     }
 }
 
 // -----------------------
 
-#[derive(Hash, Eq, Clone)]
+/// A color held as a single linear-light sRGB value. Every other representation is
+/// worked out from it the first time it's asked for and kept from then on.
+#[derive(Clone)]
 pub struct Color {
-    pub rgb: Rgb,
-    pub hsl: Hsl,
-    pub hsb: Hsb,
-    pub hex: Hex,
-    pub name: String,
+    // unclamped, so colors from wider gamuts keep their value until they're displayed
+    linear: (f32, f32, f32),
+    alpha: u8,
     pub locked: bool,
+    derived: Derived,
 }
 
-impl fmt::Debug for Color {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "Rgb: ({0}, {1}, {2}) | Hsl: ({3}, {4}, {5}) | Hsb: ({6}, {7}, {8}) | Hex: ({9}) | Name: ({10}) | Locked: ({11})\n",
-            self.rgb.r,
-            self.rgb.g,
-            self.rgb.b,
-            self.hsl.h,
-            self.hsl.s,
-            self.hsl.l,
-            self.hsb.h,
-            self.hsb.s,
-            self.hsb.b,
-            self.hex.h,
-            self.name,
-            self.locked
-        )
-    }
+#[derive(Clone, Default)]
+struct Derived {
+    rgb: OnceLock<Rgb>,
+    hsl: OnceLock<Hsl>,
+    hsb: OnceLock<Hsb>,
+    hex: OnceLock<Hex>,
+    oklab: OnceLock<Oklab>,
+    oklch: OnceLock<Oklch>,
+    cmyk: OnceLock<Cmyk>,
+    name: OnceLock<String>,
+    description: OnceLock<String>,
 }
 
+// past this oklab distance the nearest dictionary name says more about the
+// dictionary than the color, so it's described instead
+const NAME_DISTANCE: f32 = 5.0;
+
+// how far in 8 bit rgb a stored hsl, hsb or cmyk may sit from the stored rgb, half a
+// degree of hue on a vivid color moves a channel about 2
+const STORED_TOLERANCE: u8 = 3;
+
+// colors are the same when they display the same
 impl PartialEq for Color {
     fn eq(&self, other: &Color) -> bool {
-        self.rgb == other.rgb
-            && self.hsl == other.hsl
-            && self.hsb == other.hsb
-            && self.hex == other.hex
-            && self.name == other.name
-            && self.locked == other.locked
+        self.rgb() == other.rgb() && self.locked == other.locked
+    }
+}
+
+impl Eq for Color {}
+
+impl Hash for Color {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rgb().hash(state);
+        self.locked.hash(state);
+    }
+}
+
+impl fmt::Debug for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (rgb, hsl, hsb, oklab, oklch, cmyk) = (
+            self.rgb(),
+            self.hsl(),
+            self.hsb(),
+            self.oklab(),
+            self.oklch(),
+            self.cmyk(),
+        );
+        writeln!(
+            f,
+            "Rgb: ({0}, {1}, {2}) | Hsl: ({3}, {4}, {5}) | Hsb: ({6}, {7}, {8}) | Hex: ({9}) | Oklab: ({10}, {11}, {12}) | Oklch: ({13}, {14}, {15}) | Cmyk: ({16}, {17}, {18}, {19}) | Alpha: ({20}) | Name: ({21}) | Locked: ({22}) | In sRGB: ({23})",
+            rgb.r,
+            rgb.g,
+            rgb.b,
+            hsl.h,
+            hsl.s,
+            hsl.l,
+            hsb.h,
+            hsb.s,
+            hsb.b,
+            self.hex().h,
+            oklab.l,
+            oklab.a,
+            oklab.b,
+            oklch.l,
+            oklch.c,
+            oklch.h,
+            cmyk.c,
+            cmyk.m,
+            cmyk.y,
+            cmyk.k,
+            self.alpha,
+            self.name(),
+            self.locked,
+            self.in_srgb_gamut()
+        )
     }
 }
 
 impl Color {
     pub fn new(code: Encoding) -> Result<Color, PaletteError> {
-        let rgb = code.get_rgb()?;
-        let hsl = code.get_hsl()?;
-        let hsb = code.get_hsb()?;
-        let hex = code.get_hex()?;
-        let name = code.get_name()?;
-        Ok(Color {
-            rgb: rgb,
-            hsl: hsl,
-            hsb: hsb,
-            hex: hex,
-            name: name,
+        Ok(Color::from_linear_srgb(
+            code.linear_srgb_f32()?,
+            code.get_alpha(),
+        ))
+    }
+
+    pub fn from_linear_srgb(linear: (f32, f32, f32), alpha: u8) -> Color {
+        Color {
+            linear,
+            alpha,
             locked: false,
-        })
+            derived: Derived::default(),
+        }
     }
 
+    /// A color from values already worked out. The rgb is the color, the others are
+    /// kept as given without being checked against it, `from_stored` checks them.
     pub fn new_raw(rgb: Rgb, hsl: Hsl, hsb: Hsb, hex: Hex, name: String, locked: bool) -> Color {
-        Color {
-            rgb: rgb,
-            hsl: hsl,
-            hsb: hsb,
-            hex: hex,
-            name: name,
-            locked: locked,
+        let mut color = Color::from_linear_srgb(
+            (
+                srgb8_to_linear(rgb.r),
+                srgb8_to_linear(rgb.g),
+                srgb8_to_linear(rgb.b),
+            ),
+            rgb.a,
+        );
+        color.locked = locked;
+
+        let derived = &color.derived;
+        let _ = derived.rgb.set(rgb);
+        let _ = derived.hsl.set(hsl);
+        let _ = derived.hsb.set(hsb);
+        let _ = derived.hex.set(hex);
+        let _ = derived.name.set(name);
+        color
+    }
+
+    /// A color as a palette file stored it. The rgb is the color, the other values are
+    /// kept as saved so loading gives back exactly what was written, and a value that
+    /// disagrees with the rgb is an `InvalidFormat` error.
+    pub fn from_stored(
+        rgb: Rgb,
+        hsl: Hsl,
+        hsb: Hsb,
+        hex: Hex,
+        cmyk: Cmyk,
+        name: String,
+        locked: bool,
+    ) -> Result<Color, PaletteError> {
+        // stored values are rounded from the color, not from its 8 bit rgb, so they
+        // can land a little off it. a hand edit or a stale file lands further
+        let stored = [
+            ("hsl", hsl.encode(), STORED_TOLERANCE),
+            ("hsb", hsb.encode(), STORED_TOLERANCE),
+            ("hex", hex.encode(), 0),
+            ("cmyk", cmyk.encode(), STORED_TOLERANCE),
+        ];
+        for (space, encoding, tolerance) in stored {
+            let found = encoding.get_rgb()?;
+            let close = |a: u8, b: u8| a.abs_diff(b) <= tolerance;
+            if !(close(found.r, rgb.r) && close(found.g, rgb.g) && close(found.b, rgb.b)) {
+                return Err(PaletteError::InvalidFormat(format!(
+                    "{space} {encoding:?} doesn't match rgb {},{},{}",
+                    rgb.r, rgb.g, rgb.b
+                )));
+            }
         }
+
+        // keep what the palette file stored so loading gives back exactly what was saved
+        let color = Color::new_raw(rgb, hsl, hsb, hex, name, locked);
+        let _ = color.derived.cmyk.set(cmyk);
+        Ok(color)
+    }
+
+    pub fn linear_srgb(&self) -> (f32, f32, f32) {
+        self.linear
+    }
+
+    pub fn rgb(&self) -> &Rgb {
+        self.derived
+            .rgb
+            .get_or_init(|| Rgb::from_linear_srgb(self.linear).with_alpha(self.alpha))
+    }
+
+    pub fn hsl(&self) -> &Hsl {
+        self.derived
+            .hsl
+            .get_or_init(|| Hsl::from_linear_srgb(self.linear).with_alpha(self.alpha))
+    }
+
+    pub fn hsb(&self) -> &Hsb {
+        self.derived
+            .hsb
+            .get_or_init(|| Hsb::from_linear_srgb(self.linear).with_alpha(self.alpha))
+    }
+
+    pub fn hex(&self) -> &Hex {
+        self.derived
+            .hex
+            .get_or_init(|| Hex::from_linear_srgb(self.linear).with_alpha(self.alpha))
+    }
+
+    pub fn oklab(&self) -> &Oklab {
+        self.derived
+            .oklab
+            .get_or_init(|| Oklab::from_linear_srgb(self.linear))
+    }
+
+    pub fn oklch(&self) -> &Oklch {
+        self.derived
+            .oklch
+            .get_or_init(|| Oklch::from_linear_srgb(self.linear))
+    }
+
+    pub fn cmyk(&self) -> &Cmyk {
+        self.derived
+            .cmyk
+            .get_or_init(|| Cmyk::from_linear_srgb(self.linear))
+    }
+
+    /// The nearest dictionary name by the chosen metric, or the description when
+    /// nothing in the dictionary comes close in Oklab.
+    pub fn name(&self) -> &str {
+        self.derived.name.get_or_init(|| {
+            // oklab says whether any name is close enough, the chosen metric which one,
+            // the same one `Encoding::get_name` gives
+            let dictionary = named_colors::active();
+            match dictionary.nearest_n(self.rgb(), 1).first() {
+                Some((_, distance)) if *distance <= NAME_DISTANCE => dictionary
+                    .nearest(self.rgb(), color_distance::metric())
+                    .map_or_else(|_| self.description().to_string(), str::to_string),
+                _ => self.description().to_string(),
+            }
+        })
+    }
+
+    /// A name built from lightness, chroma and hue, like "dark desaturated teal".
+    pub fn description(&self) -> &str {
+        self.derived
+            .description
+            .get_or_init(|| describe(self.oklch()))
+    }
+
+    /// The `n` closest names in the active dictionary and their distance in Oklab.
+    pub fn nearest_names(&self, n: usize) -> Vec<(String, f32)> {
+        named_colors::active()
+            .nearest_n(self.rgb(), n)
+            .into_iter()
+            .map(|(name, distance)| (name.to_string(), distance))
+            .collect()
+    }
+
+    /// Names are looked up again next time, for after the dictionary changes.
+    pub fn forget_name(&mut self) {
+        self.derived.name = OnceLock::new();
+    }
+
+    /// False when the color came from a wider gamut and is mapped into srgb to display.
+    pub fn in_srgb_gamut(&self) -> bool {
+        linear_in_srgb_gamut(self.linear)
     }
 
     pub fn hex_to_string(&self) -> String {
-        format!("{}\n", self.hex.h)
+        format!("{}\n", self.hex())
     }
 
     pub fn rgb_to_string(&self) -> String {
-        format!("{},{},{}\n", self.rgb.r, self.rgb.g, self.rgb.b)
+        format!("{},{},{}\n", self.rgb().r, self.rgb().g, self.rgb().b)
     }
 
     pub fn hsl_to_string(&self) -> String {
-        format!("{},{},{}\n", self.hsl.h, self.hsl.s, self.hsl.l)
+        format!("{},{},{}\n", self.hsl().h, self.hsl().s, self.hsl().l)
     }
 
     pub fn hsb_to_string(&self) -> String {
-        format!("{},{},{}\n", self.hsb.h, self.hsb.s, self.hsb.b)
+        format!("{},{},{}\n", self.hsb().h, self.hsb().s, self.hsb().b)
     }
 
     pub fn hex_to_string_no_newline(&self) -> String {
-        format!("{}", self.hex.h)
+        self.hex().to_string()
     }
 
     pub fn rgb_to_string_no_newline(&self) -> String {
-        format!("{},{},{}", self.rgb.r, self.rgb.g, self.rgb.b)
+        format!("{},{},{}", self.rgb().r, self.rgb().g, self.rgb().b)
     }
 
     pub fn hsl_to_string_no_newline(&self) -> String {
-        format!("{},{},{}", self.hsl.h, self.hsl.s, self.hsl.l)
+        format!("{},{},{}", self.hsl().h, self.hsl().s, self.hsl().l)
     }
 
     pub fn hsb_to_string_no_newline(&self) -> String {
-        format!("{},{},{}", self.hsb.h, self.hsb.s, self.hsb.b)
+        format!("{},{},{}", self.hsb().h, self.hsb().s, self.hsb().b)
+    }
+
+    pub fn cmyk_to_string(&self) -> String {
+        format!(
+            "{},{},{},{}\n",
+            self.cmyk().c,
+            self.cmyk().m,
+            self.cmyk().y,
+            self.cmyk().k
+        )
+    }
+
+    pub fn cmyk_to_string_no_newline(&self) -> String {
+        format!(
+            "{},{},{},{}",
+            self.cmyk().c,
+            self.cmyk().m,
+            self.cmyk().y,
+            self.cmyk().k
+        )
     }
 
     pub fn remove_spaces_from_name(&self) -> String {
-        self.name.replace(" ", ",")
+        self.name().replace(" ", ",")
+    }
+
+    /// The temperature of the blackbody whose light is closest in hue.
+    pub fn correlated_color_temperature(&self) -> Result<Kelvin, PaletteError> {
+        self.rgb().encode().get_kelvin()
+    }
+
+    pub fn alpha(&self) -> u8 {
+        self.alpha
+    }
+
+    pub fn is_opaque(&self) -> bool {
+        self.alpha == OPAQUE
+    }
+
+    /// Source-over blend in gamma encoded sRGB, the same way browsers composite.
+    pub fn composite_over(&self, background: &Rgb) -> Rgb {
+        let alpha = self.alpha as f32 / 255.0;
+        let blend = |fg: u8, bg: u8| (fg as f32 * alpha + bg as f32 * (1.0 - alpha)).round() as u8;
+        Rgb::new(
+            blend(self.rgb().r, background.r),
+            blend(self.rgb().g, background.g),
+            blend(self.rgb().b, background.b),
+        )
     }
 
     pub fn color_string(&self) -> String {
+        // alpha rides along with rgb so opaque palettes keep the original format
+        let rgb = if self.is_opaque() {
+            self.rgb_to_string_no_newline()
+        } else {
+            format!("{},{}", self.rgb_to_string_no_newline(), self.alpha)
+        };
+        // palette files keep hex as the decimal they always have
         format!(
-            "{} {} {} {} {} {}\n",
-            self.rgb_to_string_no_newline(),
+            "{} {} {} {} {} {} {}\n",
+            rgb,
             self.hsl_to_string_no_newline(),
             self.hsb_to_string_no_newline(),
-            self.hex_to_string_no_newline(),
+            self.hex().h,
+            self.cmyk_to_string_no_newline(),
             self.remove_spaces_from_name(),
             self.locked
         )
     }
 
-    pub fn ratatui_color(&self) -> ratatui::style::Color {
-        ratatui::style::Color::Rgb(self.rgb.r, self.rgb.g, self.rgb.b)
+    pub fn ratatui_color(&self, support: ColorSupport) -> ratatui::style::Color {
+        support.ratatui_color(self.rgb())
     }
 
-    pub fn ratatui_text(&self) -> ratatui::style::Color {
-        if self.hsl.l <= 500 {
-            ratatui::style::Color::Rgb(255, 255, 255)
-        } else {
-            ratatui::style::Color::Rgb(0, 0, 0)
-        }
+    pub fn ratatui_color_over(
+        &self,
+        background: &Rgb,
+        support: ColorSupport,
+    ) -> ratatui::style::Color {
+        support.ratatui_color(&self.composite_over(background))
+    }
+
+    pub fn ratatui_text(&self, support: ColorSupport) -> ratatui::style::Color {
+        support.ratatui_color(&readable_text(self))
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.hex())
+    }
+}
+
+// -----------------------
+
+// rgb is the one conversion that can't fail, everything else may carry
+// channels out of range
+
+impl From<Rgb> for Color {
+    fn from(rgb: Rgb) -> Color {
+        Color::from_linear_srgb(
+            (
+                srgb8_to_linear(rgb.r),
+                srgb8_to_linear(rgb.g),
+                srgb8_to_linear(rgb.b),
+            ),
+            rgb.a,
+        )
+    }
+}
+
+impl TryFrom<Hsl> for Color {
+    type Error = PaletteError;
+
+    fn try_from(hsl: Hsl) -> Result<Color, PaletteError> {
+        Color::new(hsl.encode())
+    }
+}
+
+impl TryFrom<Hsb> for Color {
+    type Error = PaletteError;
+
+    fn try_from(hsb: Hsb) -> Result<Color, PaletteError> {
+        Color::new(hsb.encode())
+    }
+}
+
+impl TryFrom<Hex> for Color {
+    type Error = PaletteError;
+
+    fn try_from(hex: Hex) -> Result<Color, PaletteError> {
+        Color::new(hex.encode())
+    }
+}
+
+impl From<&Color> for Rgb {
+    fn from(color: &Color) -> Rgb {
+        color.rgb().clone()
+    }
+}
+
+impl From<&Color> for Hsl {
+    fn from(color: &Color) -> Hsl {
+        color.hsl().clone()
+    }
+}
+
+impl From<&Color> for Hsb {
+    fn from(color: &Color) -> Hsb {
+        color.hsb().clone()
+    }
+}
+
+impl From<&Color> for Hex {
+    fn from(color: &Color) -> Hex {
+        color.hex().clone()
+    }
+}
+
+impl From<Rgb> for Hsl {
+    fn from(rgb: Rgb) -> Hsl {
+        Hsl::from(&Color::from(rgb))
+    }
+}
+
+impl From<Rgb> for Hsb {
+    fn from(rgb: Rgb) -> Hsb {
+        Hsb::from(&Color::from(rgb))
+    }
+}
+
+impl From<Rgb> for Hex {
+    fn from(rgb: Rgb) -> Hex {
+        Hex::from(&Color::from(rgb))
+    }
+}
+
+impl TryFrom<Hsl> for Rgb {
+    type Error = PaletteError;
+
+    fn try_from(hsl: Hsl) -> Result<Rgb, PaletteError> {
+        Ok(Rgb::from(&Color::try_from(hsl)?))
+    }
+}
+
+impl TryFrom<Hsl> for Hsb {
+    type Error = PaletteError;
+
+    fn try_from(hsl: Hsl) -> Result<Hsb, PaletteError> {
+        Ok(Hsb::from(&Color::try_from(hsl)?))
+    }
+}
+
+impl TryFrom<Hsl> for Hex {
+    type Error = PaletteError;
+
+    fn try_from(hsl: Hsl) -> Result<Hex, PaletteError> {
+        Ok(Hex::from(&Color::try_from(hsl)?))
+    }
+}
+
+impl TryFrom<Hsb> for Rgb {
+    type Error = PaletteError;
+
+    fn try_from(hsb: Hsb) -> Result<Rgb, PaletteError> {
+        Ok(Rgb::from(&Color::try_from(hsb)?))
+    }
+}
+
+impl TryFrom<Hsb> for Hsl {
+    type Error = PaletteError;
+
+    fn try_from(hsb: Hsb) -> Result<Hsl, PaletteError> {
+        Ok(Hsl::from(&Color::try_from(hsb)?))
+    }
+}
+
+impl TryFrom<Hsb> for Hex {
+    type Error = PaletteError;
+
+    fn try_from(hsb: Hsb) -> Result<Hex, PaletteError> {
+        Ok(Hex::from(&Color::try_from(hsb)?))
+    }
+}
+
+impl TryFrom<Hex> for Rgb {
+    type Error = PaletteError;
+
+    fn try_from(hex: Hex) -> Result<Rgb, PaletteError> {
+        Ok(Rgb::from(&Color::try_from(hex)?))
+    }
+}
+
+impl TryFrom<Hex> for Hsl {
+    type Error = PaletteError;
+
+    fn try_from(hex: Hex) -> Result<Hsl, PaletteError> {
+        Ok(Hsl::from(&Color::try_from(hex)?))
+    }
+}
+
+impl TryFrom<Hex> for Hsb {
+    type Error = PaletteError;
+
+    fn try_from(hex: Hex) -> Result<Hsb, PaletteError> {
+        Ok(Hsb::from(&Color::try_from(hex)?))
     }
 }
@@ -0,0 +1,531 @@
+use std::str::FromStr;
+
+use crate::{
+    color_spaces::{Color, Hex, Hsb, Hsl, Rgb, WhitePoint},
+    encoding::{Encoding, linear_to_srgb8},
+    error::PaletteError,
+    named_colors,
+};
+
+// Parses the CSS Color Level 4 syntaxes into an `Encoding`:
+// hex, rgb(), hsl(), hwb(), lab(), lch(), oklab(), oklch(), color() and named colors.
+// hsb() isn't css but is accepted too, it's how the app prints hsb.
+
+impl FromStr for Encoding {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Encoding, PaletteError> {
+        parse_color(s)
+    }
+}
+
+impl FromStr for Color {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Color, PaletteError> {
+        Color::new(parse_color(s)?)
+    }
+}
+
+// any notation parses, the result is converted to the asked for space
+
+impl FromStr for Rgb {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Rgb, PaletteError> {
+        parse_color(s)?.get_rgb()
+    }
+}
+
+impl FromStr for Hsl {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Hsl, PaletteError> {
+        parse_color(s)?.get_hsl()
+    }
+}
+
+impl FromStr for Hsb {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Hsb, PaletteError> {
+        parse_color(s)?.get_hsb()
+    }
+}
+
+impl FromStr for Hex {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Hex, PaletteError> {
+        parse_color(s)?.get_hex()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    text: &'a str,
+    // byte offset into the original input
+    offset: usize,
+}
+
+impl Token<'_> {
+    fn unexpected(&self, input: &str, expected: &str) -> PaletteError {
+        PaletteError::UnexpectedToken {
+            token: self.text.to_string(),
+            column: input[..self.offset].chars().count() + 1,
+            expected: expected.to_string(),
+        }
+    }
+}
+
+pub fn parse_color(input: &str) -> Result<Encoding, PaletteError> {
+    let lowered = input.to_ascii_lowercase();
+    let trimmed = lowered.trim();
+    let start = lowered.len() - lowered.trim_start().len();
+    let whole = Token {
+        text: trimmed,
+        offset: start,
+    };
+
+    if trimmed.is_empty() {
+        return Err(whole.unexpected(input, "a color"));
+    }
+
+    if let Some(digits) = trimmed.strip_prefix('#') {
+        return parse_hex(digits, whole, input);
+    }
+
+    match trimmed.find('(') {
+        Some(open) => {
+            let function = trimmed[..open].trim_end();
+            let Some(body) = trimmed[open + 1..].strip_suffix(')') else {
+                let end = Token {
+                    text: &trimmed[trimmed.len() - 1..],
+                    offset: start + trimmed.len() - 1,
+                };
+                return Err(end.unexpected(input, "')'"));
+            };
+            let args = tokenize(body, start + open + 1);
+            parse_function(function, &args, input)
+        }
+        None => parse_name(trimmed),
+    }
+}
+
+// -----------------------
+
+fn parse_hex(digits: &str, whole: Token, input: &str) -> Result<Encoding, PaletteError> {
+    let expected = "3, 4, 6 or 8 hex digits";
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(whole.unexpected(input, expected));
+    }
+
+    let value = u32::from_str_radix(digits, 16).map_err(|_| whole.unexpected(input, expected))?;
+    // short forms repeat each digit, #abc is #aabbcc
+    let expand = |nibble: u32| (nibble << 4 | nibble) as u8;
+
+    let (r, g, b, a) = match digits.len() {
+        3 => (
+            expand(value >> 8 & 0xF),
+            expand(value >> 4 & 0xF),
+            expand(value & 0xF),
+            255,
+        ),
+        4 => (
+            expand(value >> 12 & 0xF),
+            expand(value >> 8 & 0xF),
+            expand(value >> 4 & 0xF),
+            expand(value & 0xF),
+        ),
+        6 => (
+            (value >> 16 & 0xFF) as u8,
+            (value >> 8 & 0xFF) as u8,
+            (value & 0xFF) as u8,
+            255,
+        ),
+        8 => (
+            (value >> 24 & 0xFF) as u8,
+            (value >> 16 & 0xFF) as u8,
+            (value >> 8 & 0xFF) as u8,
+            (value & 0xFF) as u8,
+        ),
+        _ => return Err(whole.unexpected(input, expected)),
+    };
+
+    Ok(Encoding::Hex((r as u32) << 16 | (g as u32) << 8 | b as u32).with_alpha(a))
+}
+
+// -----------------------
+
+fn parse_name(name: &str) -> Result<Encoding, PaletteError> {
+    if name == "transparent" {
+        return Ok(Encoding::Rgb(0, 0, 0).with_alpha(0));
+    }
+
+    // css spells every gray both ways, the dictionary only one
+    named_colors::find(name)
+        .or_else(|| named_colors::find(&name.replace("grey", "gray")))
+        .map(|(key, _)| Encoding::Name(key))
+        .ok_or_else(|| PaletteError::UnknownColorName(name.to_string()))
+}
+
+// -----------------------
+
+// commas and whitespace both separate values, a slash is kept as its own token
+fn tokenize(body: &str, offset: usize) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, c) in body.char_indices() {
+        let separator = c.is_whitespace() || c == ',' || c == '/';
+        if separator {
+            if let Some(s) = start.take() {
+                tokens.push(Token {
+                    text: &body[s..i],
+                    offset: offset + s,
+                });
+            }
+            if c == '/' {
+                tokens.push(Token {
+                    text: "/",
+                    offset: offset + i,
+                });
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        tokens.push(Token {
+            text: &body[s..],
+            offset: offset + s,
+        });
+    }
+
+    tokens
+}
+
+fn parse_function(function: &str, args: &[Token], input: &str) -> Result<Encoding, PaletteError> {
+    let (args, space) = match function {
+        "color" => match args.split_first() {
+            Some((space, rest)) => (rest, Some(*space)),
+            None => {
+                return Err(PaletteError::ArgumentCount {
+                    function: function.to_string(),
+                    expected: 4,
+                    found: 0,
+                });
+            }
+        },
+        "rgb" | "rgba" | "hsl" | "hsla" | "hsb" | "hsv" | "hwb" | "lab" | "lch" | "oklab"
+        | "oklch" => (args, None),
+        _ => return Err(PaletteError::UnknownColorFunction(function.to_string())),
+    };
+
+    let (channels, alpha) = split_alpha(function, args, input)?;
+    let alpha = match alpha {
+        Some(token) => (parse_fraction(&token, input)? * 255.0).round() as u8,
+        None => 255,
+    };
+    let [c1, c2, c3] = channels;
+
+    let encoding = match function {
+        "rgb" | "rgba" => {
+            let channel = |token: &Token| -> Result<u8, PaletteError> {
+                Ok(parse_number_or_percent(token, 255.0, input)?
+                    .clamp(0.0, 255.0)
+                    .round() as u8)
+            };
+            Encoding::Rgb(channel(&c1)?, channel(&c2)?, channel(&c3)?)
+        }
+        "hsl" | "hsla" => Encoding::Hsl(
+            parse_hue(&c1, input)?,
+            per_mille(parse_number_or_percent(&c2, 100.0, input)? / 100.0),
+            per_mille(parse_number_or_percent(&c3, 100.0, input)? / 100.0),
+        ),
+        "hsb" | "hsv" => Encoding::Hsb(
+            parse_hue(&c1, input)?,
+            per_mille(parse_number_or_percent(&c2, 100.0, input)? / 100.0),
+            per_mille(parse_number_or_percent(&c3, 100.0, input)? / 100.0),
+        ),
+        "hwb" => {
+            let mut white = parse_number_or_percent(&c2, 100.0, input)?.clamp(0.0, 100.0) / 100.0;
+            let mut black = parse_number_or_percent(&c3, 100.0, input)?.clamp(0.0, 100.0) / 100.0;
+            if white + black > 1.0 {
+                let sum = white + black;
+                white /= sum;
+                black /= sum;
+            }
+            let value = 1.0 - black;
+            let saturation = if value == 0.0 {
+                0.0
+            } else {
+                1.0 - white / value
+            };
+            Encoding::Hsb(
+                parse_hue(&c1, input)?,
+                per_mille(saturation),
+                per_mille(value),
+            )
+        }
+        "lab" => Encoding::Lab(
+            (parse_number_or_percent(&c1, 100.0, input)?.clamp(0.0, 100.0) * 10.0).round() as u16,
+            (parse_number_or_percent(&c2, 125.0, input)? * 10.0).round() as i16,
+            (parse_number_or_percent(&c3, 125.0, input)? * 10.0).round() as i16,
+        ),
+        "lch" => Encoding::Lch(
+            (parse_number_or_percent(&c1, 100.0, input)?.clamp(0.0, 100.0) * 10.0).round() as u16,
+            (parse_number_or_percent(&c2, 150.0, input)?.max(0.0) * 10.0).round() as u16,
+            parse_hue(&c3, input)?,
+        ),
+        "oklab" => Encoding::Oklab(
+            per_mille(parse_number_or_percent(&c1, 1.0, input)?),
+            (parse_number_or_percent(&c2, 0.4, input)? * 1000.0).round() as i16,
+            (parse_number_or_percent(&c3, 0.4, input)? * 1000.0).round() as i16,
+        ),
+        "oklch" => Encoding::Oklch(
+            per_mille(parse_number_or_percent(&c1, 1.0, input)?),
+            (parse_number_or_percent(&c2, 0.4, input)?.max(0.0) * 1000.0).round() as u16,
+            parse_hue(&c3, input)?,
+        ),
+        _ => {
+            let space = space.ok_or_else(|| PaletteError::UnknownColorFunction(function.into()))?;
+            let c1 = parse_number_or_percent(&c1, 1.0, input)?;
+            let c2 = parse_number_or_percent(&c2, 1.0, input)?;
+            let c3 = parse_number_or_percent(&c3, 1.0, input)?;
+            match space.text {
+                "srgb" => Encoding::Rgb(
+                    (c1.clamp(0.0, 1.0) * 255.0).round() as u8,
+                    (c2.clamp(0.0, 1.0) * 255.0).round() as u8,
+                    (c3.clamp(0.0, 1.0) * 255.0).round() as u8,
+                ),
+                "srgb-linear" => Encoding::Rgb(
+                    linear_to_srgb8(c1),
+                    linear_to_srgb8(c2),
+                    linear_to_srgb8(c3),
+                ),
+                "display-p3" => Encoding::DisplayP3(unit(c1), unit(c2), unit(c3)),
+                "rec2020" => Encoding::Rec2020(unit(c1), unit(c2), unit(c3)),
+                "xyz" | "xyz-d65" => Encoding::Xyz(WhitePoint::D65, xyz(c1), xyz(c2), xyz(c3)),
+                "xyz-d50" => Encoding::Xyz(WhitePoint::D50, xyz(c1), xyz(c2), xyz(c3)),
+                _ => return Err(space.unexpected(input, "a color space")),
+            }
+        }
+    };
+
+    Ok(encoding.with_alpha(alpha))
+}
+
+// modern syntax puts alpha after a slash, legacy syntax as a fourth comma separated value
+fn split_alpha<'a>(
+    function: &str,
+    args: &[Token<'a>],
+    input: &str,
+) -> Result<([Token<'a>; 3], Option<Token<'a>>), PaletteError> {
+    let slash = args.iter().position(|token| token.text == "/");
+    let (channels, alpha) = match slash {
+        Some(i) => {
+            let alpha = &args[i + 1..];
+            match alpha {
+                [alpha] => (&args[..i], Some(*alpha)),
+                [] => return Err(args[i].unexpected(input, "an alpha value after '/'")),
+                [_, extra, ..] => return Err(extra.unexpected(input, "')'")),
+            }
+        }
+        None if args.len() == 4 => (&args[..3], Some(args[3])),
+        None => (args, None),
+    };
+
+    match channels {
+        [c1, c2, c3] => Ok(([*c1, *c2, *c3], alpha)),
+        _ => Err(PaletteError::ArgumentCount {
+            function: function.to_string(),
+            expected: 3,
+            found: channels.len(),
+        }),
+    }
+}
+
+// -----------------------
+
+fn parse_number(token: &Token, input: &str) -> Result<f32, PaletteError> {
+    if token.text == "none" {
+        return Ok(0.0);
+    }
+    token
+        .text
+        .parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| token.unexpected(input, "a number"))
+}
+
+// percentages are scaled so 100% lands on `full`
+fn parse_number_or_percent(token: &Token, full: f32, input: &str) -> Result<f32, PaletteError> {
+    match token.text.strip_suffix('%') {
+        Some(percent) => {
+            let number = Token {
+                text: percent,
+                offset: token.offset,
+            };
+            parse_number(&number, input)
+                .map(|n| n / 100.0 * full)
+                .map_err(|_| token.unexpected(input, "a percentage"))
+        }
+        None => parse_number(token, input),
+    }
+}
+
+fn parse_fraction(token: &Token, input: &str) -> Result<f32, PaletteError> {
+    Ok(parse_number_or_percent(token, 1.0, input)?.clamp(0.0, 1.0))
+}
+
+fn parse_hue(token: &Token, input: &str) -> Result<u16, PaletteError> {
+    let units = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ];
+    let (number, scale) = units
+        .iter()
+        .find_map(|(unit, scale)| token.text.strip_suffix(unit).map(|n| (n, *scale)))
+        .unwrap_or((token.text, 1.0));
+
+    let number = Token {
+        text: number,
+        offset: token.offset,
+    };
+    let degrees = parse_number(&number, input).map_err(|_| token.unexpected(input, "a hue"))?;
+    Ok(((degrees * scale).rem_euclid(360.0).round() as u16) % 360)
+}
+
+fn per_mille(fraction: f32) -> u16 {
+    (fraction.clamp(0.0, 1.0) * 1000.0).round() as u16
+}
+
+fn unit(component: f32) -> u16 {
+    (component.clamp(0.0, 1.0) * 1000.0).round() as u16
+}
+
+fn xyz(component: f32) -> u16 {
+    (component.max(0.0) * 1000.0).round() as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_parse_color() {
+        let tests: HashMap<&str, Encoding> = HashMap::from([
+            ("#abc", Encoding::Hex(0xAABBCC)),
+            ("#ABC8", Encoding::Hex(0xAABBCC).with_alpha(0x88)),
+            ("#cd5c5c", Encoding::Hex(0xCD5C5C)),
+            ("#aabbcc80", Encoding::Hex(0xAABBCC).with_alpha(0x80)),
+            (
+                "rgb(12 34 56 / 50%)",
+                Encoding::Rgb(12, 34, 56).with_alpha(128),
+            ),
+            (
+                "rgba(12, 34, 56, 0.5)",
+                Encoding::Rgb(12, 34, 56).with_alpha(128),
+            ),
+            ("rgb(100% 0% 50%)", Encoding::Rgb(255, 0, 128)),
+            ("hsl(210deg 40% 50%)", Encoding::Hsl(210, 400, 500)),
+            ("hsl(0.5turn, 100%, 25%)", Encoding::Hsl(180, 1000, 250)),
+            ("hwb(0 0% 0%)", Encoding::Hsb(0, 1000, 1000)),
+            ("lab(54.29% 80.8 69.9)", Encoding::Lab(543, 808, 699)),
+            ("lch(54.3 106.8 40.9)", Encoding::Lch(543, 1068, 41)),
+            ("oklab(0.628 0.225 0.126)", Encoding::Oklab(628, 225, 126)),
+            ("oklch(62.8% 0.258 29.2deg)", Encoding::Oklch(628, 258, 29)),
+            ("color(srgb 1 0 0.5)", Encoding::Rgb(255, 0, 128)),
+            ("color(display-p3 1 0 0)", Encoding::DisplayP3(1000, 0, 0)),
+            (
+                "color(rec2020 0.5 0.25 1)",
+                Encoding::Rec2020(500, 250, 1000),
+            ),
+            (
+                "color(xyz-d50 0.964 1 0.825)",
+                Encoding::Xyz(WhitePoint::D50, 964, 1000, 825),
+            ),
+            (
+                "  RebeccaPurple ",
+                Encoding::Name("Rebecca Purple".to_string()),
+            ),
+            ("transparent", Encoding::Rgb(0, 0, 0).with_alpha(0)),
+        ]);
+
+        for (input, encoding) in tests {
+            println!("input: {:?}, desired result: {:?}", input, encoding);
+            let result = match input.parse::<Encoding>() {
+                Ok(encoding) => encoding,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, encoding);
+        }
+    }
+
+    #[test]
+    fn errors_point_at_the_bad_token() {
+        match "rgb(12 3x 56)".parse::<Encoding>() {
+            Err(PaletteError::UnexpectedToken { token, column, .. }) => {
+                assert_eq!(token, "3x");
+                assert_eq!(column, 8);
+            }
+            other => panic!("unexpected result {:?}", other),
+        }
+
+        assert!(matches!(
+            "hsl(10 20%)".parse::<Encoding>(),
+            Err(PaletteError::ArgumentCount { found: 2, .. })
+        ));
+        assert!(matches!(
+            "cmyk(0 0 0 0)".parse::<Encoding>(),
+            Err(PaletteError::UnknownColorFunction(_))
+        ));
+        assert!(matches!(
+            "blurple".parse::<Encoding>(),
+            Err(PaletteError::UnknownColorName(_))
+        ));
+        assert!(matches!(
+            "#12345".parse::<Encoding>(),
+            Err(PaletteError::UnexpectedToken { .. })
+        ));
+    }
+
+    #[test]
+    fn parses_straight_into_a_color() {
+        let color: Color = "#cd5c5c".parse().unwrap();
+        assert_eq!(color.name(), "Indian Red");
+    }
+
+    #[test]
+    fn display_reads_back_as_the_same_value() {
+        let rgb = Rgb::new(205, 92, 92);
+        let hsl = Hsl::new(0, 531, 582).with_alpha(128);
+        let hsb = Hsb::new(210, 400, 755);
+        let hex = Hex::new(0x0a0b0c).with_alpha(0x80);
+        assert_eq!(rgb.to_string(), "rgb(205 92 92)");
+        assert_eq!(hsl.to_string(), "hsl(0 53.1% 58.2% / 0.502)");
+        assert_eq!(hsb.to_string(), "hsb(210 40% 75.5%)");
+        assert_eq!(hex.to_string(), "#0a0b0c80");
+
+        assert_eq!(rgb.to_string().parse::<Rgb>().unwrap(), rgb);
+        assert_eq!(hsl.to_string().parse::<Hsl>().unwrap(), hsl);
+        assert_eq!(hsb.to_string().parse::<Hsb>().unwrap(), hsb);
+        assert_eq!(hex.to_string().parse::<Hex>().unwrap(), hex);
+        for a in 0..=255 {
+            let rgb = rgb.clone().with_alpha(a);
+            assert_eq!(rgb.to_string().parse::<Rgb>().unwrap(), rgb);
+        }
+
+        // any notation converts to the asked for type
+        assert_eq!("indianred".parse::<Hex>().unwrap(), Hex::new(0xcd5c5c));
+        let color: Color = "rgb(205 92 92)".parse().unwrap();
+        assert_eq!(color.to_string(), "#cd5c5c");
+        assert_eq!(color.hex_to_string(), "#cd5c5c\n");
+        assert_eq!(color.hex_to_string_no_newline(), "#cd5c5c");
+    }
+}
@@ -0,0 +1,144 @@
+use crate::color_spaces::Oklch;
+
+// oklch hues of each family's most typical color, nearest one names the hue
+const HUES: [(u16, &str); 13] = [
+    (10, "rose"),
+    (29, "red"),
+    (55, "orange"),
+    (95, "yellow"),
+    (125, "yellow-green"),
+    (145, "green"),
+    (195, "cyan"),
+    (235, "azure"),
+    (262, "blue"),
+    (282, "blue-violet"),
+    (300, "violet"),
+    (322, "purple"),
+    (345, "magenta"),
+];
+
+// below this chroma (x1000) there's no hue worth naming
+const GRAY_CHROMA: u16 = 25;
+
+/// Names a color from its lightness, chroma and hue bands, like "dark muted teal".
+/// Always has an answer, for colors nowhere near any dictionary name.
+pub fn describe(oklch: &Oklch) -> String {
+    if oklch.c < GRAY_CHROMA {
+        return gray(oklch.l).to_string();
+    }
+
+    let hue = HUES
+        .iter()
+        .min_by_key(|(center, _)| hue_distance(*center, oklch.h))
+        .map_or("red", |(_, name)| name);
+    // dark oranges and yellows read as brown and dark cyans as teal, not as
+    // shades of the lighter color
+    let hue = match (hue, oklch.l) {
+        ("orange" | "yellow", 0..600) => "brown",
+        ("cyan", 0..600) => "teal",
+        (hue, _) => hue,
+    };
+    let chroma = match oklch.c {
+        0..50 => "desaturated",
+        50..90 => "muted",
+        90..200 => "",
+        _ => "vivid",
+    };
+
+    [lightness(oklch.l), chroma, hue]
+        .into_iter()
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn gray(l: u16) -> &'static str {
+    match l {
+        0..200 => "black",
+        200..350 => "very dark gray",
+        350..500 => "dark gray",
+        500..700 => "gray",
+        700..850 => "light gray",
+        850..970 => "very light gray",
+        _ => "white",
+    }
+}
+
+fn lightness(l: u16) -> &'static str {
+    match l {
+        0..300 => "very dark",
+        300..450 => "dark",
+        450..700 => "",
+        700..850 => "light",
+        _ => "pale",
+    }
+}
+
+fn hue_distance(a: u16, b: u16) -> u16 {
+    let difference = a.abs_diff(b) % 360;
+    difference.min(360 - difference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color_spaces::Color, encoding::Encoding};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_describe() {
+        let tests: HashMap<Encoding, &str> = HashMap::from([
+            (Encoding::Rgb(0, 0, 0), "black"),
+            (Encoding::Rgb(128, 128, 128), "gray"),
+            (Encoding::Rgb(255, 255, 255), "white"),
+            (Encoding::Rgb(255, 0, 0), "vivid red"),
+            (Encoding::Rgb(0, 0, 255), "vivid blue"),
+            (Encoding::Rgb(0, 128, 128), "teal"),
+            (Encoding::Rgb(47, 79, 79), "dark desaturated teal"),
+            (Encoding::Rgb(139, 69, 19), "brown"),
+            (Encoding::Rgb(80, 40, 20), "dark muted brown"),
+            (Encoding::Rgb(104, 88, 120), "muted violet"),
+            (Encoding::Rgb(200, 200, 250), "light muted blue-violet"),
+            (Encoding::Rgb(255, 255, 0), "pale vivid yellow"),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match input.get_oklch() {
+                Ok(oklch) => assert_eq!(describe(&oklch), expected),
+                Err(e) => panic!("{e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn describes_colors_no_name_fits() {
+        // close to a dictionary color, keeps its name
+        let indian_red = Color::new(Encoding::Rgb(205, 91, 93)).unwrap();
+        assert_eq!(indian_red.name(), "Indian Red");
+        assert_eq!(indian_red.nearest_names(1)[0].0, indian_red.name());
+        assert_eq!(indian_red.description(), "red");
+
+        // nearest is Dim Gray, which it plainly isn't
+        let dusky_purple = Color::new(Encoding::Rgb(104, 88, 120)).unwrap();
+        assert_eq!(dusky_purple.name(), "muted violet");
+        assert_eq!(dusky_purple.name(), dusky_purple.description());
+    }
+
+    #[test]
+    fn names_agree_with_encoding_names() {
+        let tests = vec![
+            Encoding::Rgb(205, 91, 93),
+            Encoding::Rgb(250, 128, 114),
+            Encoding::Rgb(70, 130, 180),
+            Encoding::Rgb(34, 139, 34),
+            Encoding::Rgb(240, 230, 140),
+        ];
+
+        for input in tests {
+            let color = Color::new(input.clone()).unwrap();
+            println!("input: {:?}, desired result: {:?}", input, color.name());
+            assert_eq!(input.get_name().unwrap(), color.name());
+        }
+    }
+}
@@ -1,504 +1,393 @@
 use crate::color_spaces::*;
-use crate::named_colors::NAMED_COLORS;
-use crate::{color_math::three_node_distance_rgb, error::PaletteError};
-use std::cmp::{max, min};
+use crate::named_colors;
+use crate::{
+    color_distance::{self, DistanceMetric, ok_distance},
+    error::PaletteError,
+};
 
-#[derive(Hash, Eq, Debug)]
+#[derive(Hash, PartialEq, Eq, Debug, Clone)]
 pub enum Encoding {
     Rgb(u8, u8, u8),
     Hsl(u16, u16, u16),
     Name(String),
     Hsb(u16, u16, u16),
     Hex(u32),
-}
-
-impl PartialEq for Encoding {
-    fn eq(&self, other: &Self) -> bool {
-        match (self, other) {
-            (Encoding::Rgb(r1, g1, b1), Encoding::Rgb(r2, g2, b2)) => {
-                r1 == r2 && g1 == g2 && b1 == b2
-            }
-            (Encoding::Hsl(h1, s1, l1), Encoding::Hsl(h2, s2, l2)) => {
-                h1 == h2 && s1 == s2 && l1 == l2
-            }
-            (Encoding::Name(n1), Encoding::Name(n2)) => n1 == n2,
-            (Encoding::Hsb(h1, s1, b1), Encoding::Hsb(h2, s2, b2)) => {
-                h1 == h2 && s1 == s2 && b1 == b2
-            }
-            (Encoding::Hex(h1), Encoding::Hex(h2)) => h1 == h2,
-            _ => false,
-        }
-    }
+    Oklab(u16, i16, i16),
+    Oklch(u16, u16, u16),
+    Xyz(WhitePoint, u16, u16, u16),
+    Lab(u16, i16, i16),
+    Lch(u16, u16, u16),
+    Cmyk(u16, u16, u16, u16),
+    DisplayP3(u16, u16, u16),
+    Rec2020(u16, u16, u16),
+    Kelvin(u16),
+    Ansi256(u8),
+    Hsluv(u16, u16, u16),
+    Hpluv(u16, u16, u16),
+    Alpha(Box<Encoding>, u8),
 }
 
 // -----------------------
 
 impl Encoding {
-    fn translate_to_rgb(&self) -> Result<Encoding, PaletteError> {
+    /// Wraps the encoding with an alpha channel, opaque colors are left bare.
+    pub fn with_alpha(self, a: u8) -> Encoding {
         match self {
-            Encoding::Rgb(r, g, b) => Ok(Encoding::Rgb(*r, *g, *b)),
-
-            // -----------------------
-            Encoding::Hsl(h, s, l) => {
-                assert!(*h <= 360);
-                assert!(*s <= 1000);
-                assert!(*l <= 1000);
-
-                let region = *h / 60;
-                let h = *h as f32;
-                let s = *s as f32 / 1000.0;
-                let l = *l as f32 / 1000.0;
-
-                let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-                let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-                let m = l - c / 2.0;
-
-                let (r, g, b) = match region {
-                    0 => (c, x, 0.0),
-                    1 => (x, c, 0.0),
-                    2 => (0.0, c, x),
-                    3 => (0.0, x, c),
-                    4 => (x, 0.0, c),
-                    _ => (c, 0.0, x),
-                };
-                let (r, g, b) = (
-                    ((r + m) * 255.0).round() as u8,
-                    ((g + m) * 255.0).round() as u8,
-                    ((b + m) * 255.0).round() as u8,
-                );
-                Ok(Encoding::Rgb(r, g, b))
-            }
-
-            // -----------------------
-            Encoding::Name(name) => {
-                let (r, g, b) = match NAMED_COLORS.get(name.as_str()) {
-                    Some(rgb) => *rgb,
-                    None => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "failed to get rgb from {name}".to_string(),
-                        ));
-                    }
-                };
-                Ok(Encoding::Rgb(r, g, b))
-            }
-
-            // -----------------------
-            Encoding::Hsb(h, s, b) => {
-                assert!(*h <= 360);
-                assert!(*s <= 1000);
-                assert!(*b <= 1000);
-
-                let region = h / 60;
-                let h = *h as f32;
-                let s = *s as f32 / 1000.0;
-                let b = *b as f32 / 1000.0;
-
-                let c = b * s;
-                let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
-                let m = b - c;
-
-                let (r, g, b) = match region {
-                    0 => (c, x, 0.0),
-                    1 => (x, c, 0.0),
-                    2 => (0.0, c, x),
-                    3 => (0.0, x, c),
-                    4 => (x, 0.0, c),
-                    _ => (c, 0.0, x),
-                };
-
-                let (r, g, b) = (
-                    ((r + m) * 255.0).round() as u8,
-                    ((g + m) * 255.0).round() as u8,
-                    ((b + m) * 255.0).round() as u8,
-                );
-                Ok(Encoding::Rgb(r, g, b))
-            }
+            Encoding::Alpha(inner, _) => inner.with_alpha(a),
+            _ if a == OPAQUE => self,
+            _ => Encoding::Alpha(Box::new(self), a),
+        }
+    }
 
-            // -----------------------
-            Encoding::Hex(h) => {
-                let r = ((h >> 16) & 0xFF) as u8;
-                let g = ((h >> 8) & 0xFF) as u8;
-                let b = (h & 0xFF) as u8;
-                Ok(Encoding::Rgb(r, g, b))
-            }
+    pub fn get_alpha(&self) -> u8 {
+        match self {
+            Encoding::Alpha(_, a) => *a,
+            _ => OPAQUE,
         }
     }
 
     // -----------------------
 
-    fn translate_to_hsl(&self) -> Result<Encoding, PaletteError> {
+    /// Converts through the linear sRGB hub, an encoding already in the target space
+    /// is checked and returned as is. Alpha is left for the caller to carry over.
+    pub fn convert<S: ColorSpace>(&self) -> Result<S, PaletteError> {
         match self {
-            Encoding::Rgb(r, g, b) => {
-                let r = ((*r as f32 / 255.0) * 1000.0).round();
-                let g = ((*g as f32 / 255.0) * 1000.0).round();
-                let b = ((*b as f32 / 255.0) * 1000.0).round();
-                let bigger = max(r as i32, b as i32);
-                let smaller = min(r as i32, b as i32);
-                let c_max = max(bigger, g as i32) as f32;
-                let c_min = min(smaller, g as i32) as f32;
-                let delta = c_max - c_min;
-
-                let l = (c_max + c_min) / 2.0;
-
-                let h = if delta == 0.0 {
-                    0.0
-                } else if c_max == r {
-                    60.0 * ((g - b) / delta % 6.0)
-                } else if c_max == g {
-                    60.0 * ((b - r) / delta + 2.0)
-                } else if c_max == b {
-                    60.0 * ((r - g) / delta + 4.0)
-                } else {
-                    return Err(PaletteError::UntranslatableEncoding(format!(
-                        "c_max ({c_max}) does not match r ({r}), g ({g}), or b ({b})"
-                    )));
-                };
-
-                let s = if delta == 0.0 {
-                    0.0
-                } else {
-                    delta / (1.0 - (2.0 * (l / 1000.0) - 1.0).abs())
-                };
-
-                Ok(Encoding::Hsl(
-                    h.round() as u16,
-                    s.round() as u16,
-                    l.round() as u16,
-                ))
-            }
-            Encoding::Hsl(h, s, l) => Ok(Encoding::Hsl(*h, *s, *l)),
-            Encoding::Name(name) => {
-                let (r, g, b) = match NAMED_COLORS.get(name.as_str()) {
-                    Some(rgb) => *rgb,
-                    None => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "failed to get rgb from {name}".to_string(),
-                        ));
-                    }
-                };
-                match Encoding::Rgb(r, g, b).translate_to_hsl() {
-                    Ok(hsl) => Ok(hsl),
-                    Err(e) => Err(e),
+            Encoding::Alpha(inner, _) => inner.convert(),
+            _ => match S::from_encoding(self) {
+                Some(value) => {
+                    value.validate()?;
+                    Ok(value)
                 }
-            }
-            Encoding::Hsb(h, s, b) => {
-                assert!(*h <= 360);
-                assert!(*s <= 1000);
-                assert!(*b <= 1000);
-                let h = *h;
-                let s = *s;
-                let b = *b;
-
-                let l = b * (1000 - (s / 2));
-                let s = if l == 0 || l == 1 {
-                    0
-                } else {
-                    (b - l) / (min(l, 1000 - l))
-                };
-
-                Ok(Encoding::Hsl(h, s, l))
-            }
-            Encoding::Hex(h) => {
-                let r = ((h >> 16) & 0xFF) as u8;
-                let g = ((h >> 8) & 0xFF) as u8;
-                let b = (h & 0xFF) as u8;
-
-                let r = ((r as f32 / 255.0) * 1000.0).round();
-                let g = ((g as f32 / 255.0) * 1000.0).round();
-                let b = ((b as f32 / 255.0) * 1000.0).round();
-                let bigger = max(r as i32, b as i32);
-                let smaller = min(r as i32, b as i32);
-                let c_max = max(bigger, g as i32) as f32;
-                let c_min = min(smaller, g as i32) as f32;
-                let delta = c_max - c_min;
-
-                let l = (c_max + c_min) / 2.0;
-
-                let h = if delta == 0.0 {
-                    0.0
-                } else if c_max == r {
-                    60.0 * ((g - b) / delta % 6.0)
-                } else if c_max == g {
-                    60.0 * ((b - r) / delta + 2.0)
-                } else if c_max == b {
-                    60.0 * ((r - g) / delta + 4.0)
-                } else {
-                    return Err(PaletteError::UntranslatableEncoding(
-                        "c_max ({c_max}) does not match r ({r}), g ({g}), or b ({b})".to_string(),
-                    ));
-                };
-
-                let s = if delta == 0.0 {
-                    0.0
-                } else {
-                    delta / (1.0 - (2.0 * (l / 1000.0) - 1.0).abs())
-                };
-
-                Ok(Encoding::Hsl(
-                    h.round() as u16,
-                    s.round() as u16,
-                    l.round() as u16,
-                ))
-            }
+                None => Ok(S::from_linear_srgb(self.linear_srgb_f32()?)),
+            },
         }
     }
 
     // -----------------------
 
-    fn translate_to_hsb(&self) -> Result<Encoding, PaletteError> {
+    // unclamped linear srgb, values outside 0..1 are outside the srgb gamut
+    pub(crate) fn linear_srgb_f32(&self) -> Result<(f32, f32, f32), PaletteError> {
         match self {
-            Encoding::Rgb(r, g, b) => {
-                let red = ((*r as f32 / 255.0) * 1000.0).round();
-                let green = ((*g as f32 / 255.0) * 1000.0).round();
-                let blue = ((*b as f32 / 255.0) * 1000.0).round();
-                let bigger = max(red as i32, blue as i32);
-                let smaller = min(red as i32, blue as i32);
-                let c_max = max(bigger, green as i32) as f32;
-                let c_min = min(smaller, green as i32) as f32;
-                let delta = c_max - c_min;
-
-                let h = if c_max.round() as i32 == red.round() as i32 {
-                    60.0 * ((green - blue) / delta % 6.0)
-                } else if c_max == green {
-                    60.0 * ((blue - red) / delta + 2.0)
-                } else if c_max == blue {
-                    60.0 * ((red - green) / delta + 4.0)
-                } else {
-                    0.0
-                };
+            Encoding::Alpha(inner, _) => inner.linear_srgb_f32(),
+            Encoding::Rgb(r, g, b) => Rgb::new(*r, *g, *b).to_linear_srgb(),
+            Encoding::Hsl(h, s, l) => Hsl::new(*h, *s, *l).to_linear_srgb(),
+            Encoding::Hsb(h, s, b) => Hsb::new(*h, *s, *b).to_linear_srgb(),
+            Encoding::Hex(h) => Hex::new(*h).to_linear_srgb(),
+            Encoding::Oklab(l, a, b) => Oklab::new(*l, *a, *b).to_linear_srgb(),
+            Encoding::Oklch(l, c, h) => Oklch::new(*l, *c, *h).to_linear_srgb(),
+            Encoding::Xyz(w, x, y, z) => Xyz::new(*w, *x, *y, *z).to_linear_srgb(),
+            Encoding::Lab(l, a, b) => Lab::new(*l, *a, *b).to_linear_srgb(),
+            Encoding::Lch(l, c, h) => Lch::new(*l, *c, *h).to_linear_srgb(),
+            Encoding::Cmyk(c, m, y, k) => Cmyk::new(*c, *m, *y, *k).to_linear_srgb(),
+            Encoding::DisplayP3(r, g, b) => DisplayP3::new(*r, *g, *b).to_linear_srgb(),
+            Encoding::Rec2020(r, g, b) => Rec2020::new(*r, *g, *b).to_linear_srgb(),
+            Encoding::Hsluv(h, s, l) => Hsluv::new(*h, *s, *l).to_linear_srgb(),
+            Encoding::Hpluv(h, s, l) => Hpluv::new(*h, *s, *l).to_linear_srgb(),
+            Encoding::Name(name) => named_colors::find(name)
+                .ok_or_else(|| PaletteError::UnknownColorName(name.to_string()))?
+                .1
+                .to_linear_srgb(),
+            Encoding::Ansi256(i) => Ansi256::new(*i).to_linear_srgb(),
+            Encoding::Kelvin(k) => Kelvin::new(*k).to_linear_srgb(),
+        }
+    }
 
-                let s = if c_max == 0.0 {
-                    0.0
-                } else {
-                    (delta / c_max) * 1000.0
-                };
-                let b = c_max;
-
-                Ok(Encoding::Hsb(
-                    h.round() as u16,
-                    s.round() as u16,
-                    b.round() as u16,
-                ))
-            }
-            Encoding::Hsl(h, s, l) => {
-                assert!(*h <= 360);
-                assert!(*s <= 1000);
-                assert!(*l <= 1000);
-                let h = *h;
-                let l = *l as u32;
-                let old_s = *s as u32;
-                let b = l + (old_s * min(l, 1000 - l)) / 1000;
-                let s = if b == 0 { 0 } else { (2000 * (b - l)) / b };
-                Ok(Encoding::Hsb(h, s as u16, b as u16))
-            }
-            Encoding::Name(name) => {
-                let (r, g, b) = match NAMED_COLORS.get(name.as_str()) {
-                    Some(rgb) => *rgb,
-                    None => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "failed to get rgb from {name}".to_string(),
-                        ));
-                    }
-                };
-                Encoding::Rgb(r, g, b).translate_to_hsb()
-            }
-            Encoding::Hsb(h, s, b) => Ok(Encoding::Hsb(*h, *s, *b)),
-            Encoding::Hex(hex) => {
-                let r = ((hex >> 16) & 0xFF) as u8;
-                let g = ((hex >> 8) & 0xFF) as u8;
-                let b = (hex & 0xFF) as u8;
-
-                let red = ((r as f32 / 255.0) * 1000.0).round();
-                let green = ((g as f32 / 255.0) * 1000.0).round();
-                let blue = ((b as f32 / 255.0) * 1000.0).round();
-
-                let bigger = max(red as i32, blue as i32);
-                let smaller = min(red as i32, blue as i32);
-                let c_max = max(bigger, green as i32) as f32;
-                let c_min = min(smaller, green as i32) as f32;
-                let delta = c_max - c_min;
-
-                let h = if c_max == red {
-                    60.0 * ((green - blue) / delta % 6.0)
-                } else if c_max == green {
-                    60.0 * ((blue - red) / delta + 2.0)
-                } else if c_max == blue {
-                    60.0 * ((red - green) / delta + 4.0)
-                } else {
-                    0.0
-                };
+    // -----------------------
 
-                let s = if c_max == 0.0 {
-                    0.0
-                } else {
-                    (delta / c_max) * 1000.0
-                };
-                let b = c_max;
-
-                Ok(Encoding::Hsb(
-                    h.round() as u16,
-                    s.round() as u16,
-                    b.round() as u16,
-                ))
+    fn translate_to_name(&self, metric: DistanceMetric) -> Result<Encoding, PaletteError> {
+        match self {
+            Encoding::Name(name) => Ok(Encoding::Name(name.to_string())),
+            _ => {
+                let goal = self.get_rgb()?;
+                let name = named_colors::active().nearest(&goal, metric)?.to_string();
+                Ok(Encoding::Name(name))
             }
         }
     }
 
     // -----------------------
 
-    fn rgb_to_hex(&self) -> Result<Encoding, PaletteError> {
-        match self {
-            Encoding::Rgb(r, g, b) => Ok(Encoding::Hex(
-                (*r as u32) << 16 | (*g as u32) << 8 | (*b as u32),
-            )),
+    // unquantized xyz so lab and rgb don't compound rounding through the u16 form
+    fn xyz_f32(&self, white: WhitePoint) -> Result<(f32, f32, f32), PaletteError> {
+        let (xyz, from) = match self {
+            Encoding::Alpha(inner, _) => return inner.xyz_f32(white),
+            Encoding::Xyz(from, x, y, z) => (
+                (*x as f32 / 1000.0, *y as f32 / 1000.0, *z as f32 / 1000.0),
+                *from,
+            ),
+            Encoding::Lab(_, _, _) | Encoding::Lch(_, _, _) => {
+                let (l, a, b) = self.lab_f32()?;
+                (lab_to_xyz_d50(l, a, b), WhitePoint::D50)
+            }
             _ => {
-                return Err(PaletteError::UntranslatableEncoding(format!(
-                    "rgb to hex was given the wrong encoding type: {:?}",
-                    self
-                )));
+                let (r, g, b) = self.linear_srgb_f32()?;
+                (linear_srgb_to_xyz_d65(r, g, b), WhitePoint::D65)
             }
-        }
+        };
+
+        Ok(bradford_adapt(xyz, from, white))
+    }
+
+    // -----------------------
+
+    /// Whether the color fits in srgb without gamut mapping, allowing for the
+    /// rounding of the stored channels.
+    pub fn in_srgb_gamut(&self) -> Result<bool, PaletteError> {
+        Ok(linear_in_srgb_gamut(self.linear_srgb_f32()?))
+    }
+
+    // -----------------------
+
+    /// Chromatic adaptation of the color from one illuminant to another, both given as
+    /// temperatures. Returns gamut mapped rgb.
+    pub(crate) fn adapt_between_kelvin(
+        &self,
+        from: f32,
+        to: f32,
+    ) -> Result<Encoding, PaletteError> {
+        let xyz = self.xyz_f32(WhitePoint::D65)?;
+        let (x, y, z) = bradford_adapt_xyz(xyz, kelvin_to_xyz(from), kelvin_to_xyz(to));
+        // dim everything so the new white is displayable instead of clipping back to white
+        let (white_x, white_y, white_z) = kelvin_to_xyz(to);
+        let (wr, wg, wb) = xyz_d65_to_linear_srgb(white_x, white_y, white_z);
+        let scale = 1.0 / wr.max(wg).max(wb);
+        let (r, g, b) = xyz_d65_to_linear_srgb(x, y, z);
+        Ok(Rgb::from_linear_srgb((r * scale, g * scale, b * scale))
+            .with_alpha(self.get_alpha())
+            .encode())
     }
 
     // -----------------------
 
-    fn translate_to_name(&self) -> Result<Encoding, PaletteError> {
+    fn translate_to_ansi16(&self) -> Result<Encoding, PaletteError> {
         match self {
-            Encoding::Name(name) => Ok(Encoding::Name(name.to_string())),
+            Encoding::Ansi256(i) if *i < 16 => Ok(Encoding::Ansi256(*i)),
+            Encoding::Alpha(inner, _) => inner.translate_to_ansi16(),
             _ => {
                 let rgb = self.get_rgb()?;
-                let mut name: Encoding = Encoding::Rgb(0, 0, 0);
-                let mut min_distance = u32::MAX;
-                for (key, (r, g, b)) in NAMED_COLORS.iter() {
-                    let start = Rgb::new(*r, *g, *b);
-                    let goal = Rgb::new(rgb.r, rgb.g, rgb.b);
-                    if start == goal {
-                        name = Encoding::Name(String::from(*key));
-                        break;
-                    } else {
-                        let new_distance = three_node_distance_rgb(goal, start);
-                        if new_distance < min_distance {
-                            min_distance = new_distance;
-                            name = Encoding::Name(String::from(*key));
-                        }
+                let mut nearest = 0;
+                let mut min_distance = f32::MAX;
+                for i in 0..16 {
+                    let (r, g, b) = ansi256_to_rgb(i);
+                    let distance = DistanceMetric::Oklab.distance_rgb(&rgb, &Rgb::new(r, g, b))?;
+                    if distance < min_distance {
+                        min_distance = distance;
+                        nearest = i;
                     }
                 }
-                Ok(name)
+                Ok(Encoding::Ansi256(nearest))
             }
         }
     }
 
     // -----------------------
 
-    pub fn get_rgb(&self) -> Result<Rgb, PaletteError> {
+    pub(crate) fn lab_f32(&self) -> Result<(f32, f32, f32), PaletteError> {
         match self {
-            Encoding::Rgb(r, g, b) => Ok(Rgb::new(*r, *g, *b)),
+            Encoding::Alpha(inner, _) => inner.lab_f32(),
+            Encoding::Lab(l, a, b) => {
+                let lab = Lab::new(*l, *a, *b);
+                lab.validate()?;
+                Ok(lab.components())
+            }
+            Encoding::Lch(l, c, h) => {
+                let lch = Lch::new(*l, *c, *h);
+                lch.validate()?;
+                Ok(lch.lab_components())
+            }
             _ => {
-                let rgb = self.translate_to_rgb()?;
-                match rgb {
-                    Encoding::Rgb(r, g, b) => Ok(Rgb::new(r, g, b)),
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "could not translate to rgb".to_string(),
-                        ));
-                    }
-                }
+                let (x, y, z) = self.xyz_f32(WhitePoint::D50)?;
+                Ok(xyz_d50_to_lab(x, y, z))
             }
         }
     }
 
     // -----------------------
 
-    pub fn get_hsl(&self) -> Result<Hsl, PaletteError> {
-        match self {
-            Encoding::Hsl(h, s, l) => Ok(Hsl::new(*h, *s, *l)),
-            _ => {
-                let hsl = self.translate_to_hsl()?;
-                match hsl {
-                    Encoding::Hsl(h, s, l) => Ok(Hsl::new(h, s, l)),
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "could not translate to hsl".to_string(),
-                        ));
-                    }
+    /// `ink_limit` is the maximum total coverage in tenths of a percent (3000 = 300%).
+    /// Over the limit, cyan, magenta and yellow are scaled back and black is kept.
+    fn translate_to_cmyk(&self, ink_limit: Option<u16>) -> Result<Encoding, PaletteError> {
+        let cmyk: Cmyk = self.convert()?;
+        let (c, m, y, k) = (
+            cmyk.c as f32 / 1000.0,
+            cmyk.m as f32 / 1000.0,
+            cmyk.y as f32 / 1000.0,
+            cmyk.k as f32 / 1000.0,
+        );
+
+        let (c, m, y) = match ink_limit {
+            Some(limit) => {
+                let limit = limit as f32 / 1000.0;
+                let total = c + m + y + k;
+                if total > limit && c + m + y > 0.0 {
+                    let scale = ((limit - k) / (c + m + y)).max(0.0);
+                    (c * scale, m * scale, y * scale)
+                } else {
+                    (c, m, y)
                 }
             }
-        }
+            None => (c, m, y),
+        };
+
+        Ok(Encoding::Cmyk(
+            (c * 1000.0).round() as u16,
+            (m * 1000.0).round() as u16,
+            (y * 1000.0).round() as u16,
+            (k * 1000.0).round() as u16,
+        ))
+    }
+
+    // -----------------------
+
+    pub fn get_rgb(&self) -> Result<Rgb, PaletteError> {
+        Ok(self.convert::<Rgb>()?.with_alpha(self.get_alpha()))
+    }
+
+    // -----------------------
+
+    pub fn get_hsl(&self) -> Result<Hsl, PaletteError> {
+        Ok(self.convert::<Hsl>()?.with_alpha(self.get_alpha()))
     }
 
     // -----------------------
 
     pub fn get_hsb(&self) -> Result<Hsb, PaletteError> {
-        match self {
-            Encoding::Hsb(h, s, b) => Ok(Hsb::new(*h, *s, *b)),
-            _ => {
-                let hsb = self.translate_to_hsb()?;
-                match hsb {
-                    Encoding::Hsb(h, s, b) => Ok(Hsb::new(h, s, b)),
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "could not translate to hsb".to_string(),
-                        ));
-                    }
-                }
-            }
-        }
+        Ok(self.convert::<Hsb>()?.with_alpha(self.get_alpha()))
     }
 
     // -----------------------
 
     pub fn get_hex(&self) -> Result<Hex, PaletteError> {
+        Ok(self.convert::<Hex>()?.with_alpha(self.get_alpha()))
+    }
+
+    // -----------------------
+
+    pub fn get_oklab(&self) -> Result<Oklab, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_oklch(&self) -> Result<Oklch, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_xyz(&self, white: WhitePoint) -> Result<Xyz, PaletteError> {
         match self {
-            Encoding::Hex(h) => Ok(Hex::new(*h)),
-            Encoding::Rgb(_, _, _) => {
-                let hex = self.rgb_to_hex()?;
-                match hex {
-                    Encoding::Hex(h) => Ok(Hex::new(h)),
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "1 could not translate rgb to hex".to_string(),
-                        ));
-                    }
-                }
-            }
+            Encoding::Xyz(w, x, y, z) if *w == white => Ok(Xyz::new(*w, *x, *y, *z)),
             _ => {
-                let rgb = self.translate_to_rgb()?;
-                match rgb {
-                    Encoding::Rgb(_, _, _) => {
-                        let hex = rgb.rgb_to_hex()?;
-                        match hex {
-                            Encoding::Hex(h) => Ok(Hex::new(h)),
-                            _ => {
-                                return Err(PaletteError::UntranslatableEncoding(
-                                    "2 could not translate rgb to hex".to_string(),
-                                ));
-                            }
-                        }
-                    }
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "could not translate to rgb while trying to get hex".to_string(),
-                        ));
-                    }
-                }
+                let (x, y, z) = self.xyz_f32(white)?;
+                Ok(Xyz::new(
+                    white,
+                    (x * 1000.0).round() as u16,
+                    (y * 1000.0).round() as u16,
+                    (z * 1000.0).round() as u16,
+                ))
             }
         }
     }
 
     // -----------------------
 
+    pub fn get_lab(&self) -> Result<Lab, PaletteError> {
+        let (l, a, b) = self.lab_f32()?;
+        Ok(Lab::from_components(l, a, b))
+    }
+
+    // -----------------------
+
+    pub fn get_lch(&self) -> Result<Lch, PaletteError> {
+        let (l, a, b) = self.lab_f32()?;
+        Ok(Lch::from_lab_components(l, a, b))
+    }
+
+    // -----------------------
+
+    pub fn get_cmyk(&self) -> Result<Cmyk, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_cmyk_with_ink_limit(&self, ink_limit: u16) -> Result<Cmyk, PaletteError> {
+        match self.translate_to_cmyk(Some(ink_limit))? {
+            Encoding::Cmyk(c, m, y, k) => Ok(Cmyk::new(c, m, y, k)),
+            _ => Err(PaletteError::UntranslatableEncoding(
+                "could not translate to cmyk".to_string(),
+            )),
+        }
+    }
+
+    // -----------------------
+
+    pub fn get_kelvin(&self) -> Result<Kelvin, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_hsluv(&self) -> Result<Hsluv, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_hpluv(&self) -> Result<Hpluv, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_ansi256(&self) -> Result<Ansi256, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    /// The nearest of the 16 base colors, as xterm draws them by default.
+    pub fn get_ansi16(&self) -> Result<Ansi256, PaletteError> {
+        match self.translate_to_ansi16()? {
+            Encoding::Ansi256(i) => Ok(Ansi256::new(i)),
+            _ => Err(PaletteError::UntranslatableEncoding(
+                "could not translate to ansi 16".to_string(),
+            )),
+        }
+    }
+
+    // -----------------------
+
+    pub fn get_display_p3(&self) -> Result<DisplayP3, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    pub fn get_rec2020(&self) -> Result<Rec2020, PaletteError> {
+        self.convert()
+    }
+
+    // -----------------------
+
+    /// The nearest name by the metric `color_distance::metric` picks.
     pub fn get_name(&self) -> Result<String, PaletteError> {
+        self.get_name_by(color_distance::metric())
+    }
+
+    // -----------------------
+
+    pub fn get_name_by(&self, metric: DistanceMetric) -> Result<String, PaletteError> {
         match self {
             Encoding::Name(n) => Ok(String::from(n)),
             _ => {
-                let name = &self.translate_to_name()?;
+                let name = &self.translate_to_name(metric)?;
                 match name {
                     Encoding::Name(n) => Ok(String::from(n)),
-                    _ => {
-                        return Err(PaletteError::UntranslatableEncoding(
-                            "could not translate to name".to_string(),
-                        ));
-                    }
+                    _ => Err(PaletteError::UntranslatableEncoding(
+                        "could not translate to name".to_string(),
+                    )),
                 }
             }
         }
@@ -507,71 +396,1818 @@ impl Encoding {
 
 // -----------------------
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
-    // use crate::color_spaces::*;
+/// A color model that converts to and from linear sRGB, the hub every conversion
+/// between two spaces goes through. Adding a space means implementing this once
+/// instead of a match arm in every other conversion.
+///
+/// Names aren't a space, what a color is called depends on the active dictionary
+/// and the distance metric, and the lookup can fail. They stay in `get_name_by`.
+pub trait ColorSpace: Sized {
+    /// Unclamped, values outside 0..1 are outside the sRGB gamut. Channels out of
+    /// their stored range are an error.
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError>;
 
-    #[test]
-    fn test_get_name() {
-        let tests: HashMap<Encoding, String> = HashMap::from([
-            (Encoding::Rgb(205, 92, 92), String::from("Indian Red")),
-            (Encoding::Rgb(205, 91, 93), String::from("Indian Red")),
-            // don't know how it translates, so can't test off by ones
-            (Encoding::Hsl(0, 531, 582), String::from("Indian Red")),
-            (Encoding::Hsb(0, 551, 804), String::from("Indian Red")),
-        ]);
+    /// Spaces that can't hold colors outside sRGB map them into the gamut first.
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Self;
 
-        for (encoding, name) in tests {
-            println!("input: {:?}, desired result: {:?}", encoding, name);
-            let result = match encoding.get_name() {
-                Ok(name) => name,
-                Err(e) => panic!("{e}"),
-            };
-            assert_eq!(result, name);
+    /// The value as is when the encoding is already in this space.
+    fn from_encoding(code: &Encoding) -> Option<Self>;
+
+    /// Channels out of their stored range are an error, the same ranges `try_new`
+    /// checks. Spaces without a `try_new` take any value.
+    fn validate(&self) -> Result<(), PaletteError> {
+        Ok(())
+    }
+}
+
+// the display spaces work on gamma encoded srgb inside the gamut
+fn display_srgb(linear: (f32, f32, f32)) -> (f32, f32, f32) {
+    let (r, g, b) = gamut_map_srgb(linear);
+    (srgb_transfer(r), srgb_transfer(g), srgb_transfer(b))
+}
+
+fn srgb_to_linear((r, g, b): (f32, f32, f32)) -> (f32, f32, f32) {
+    (
+        srgb_transfer_inverse(r),
+        srgb_transfer_inverse(g),
+        srgb_transfer_inverse(b),
+    )
+}
+
+fn hue(degrees: f32) -> u16 {
+    degrees.round() as u16 % 360
+}
+
+fn per_mille(fraction: f32) -> u16 {
+    (fraction.clamp(0.0, 1.0) * 1000.0).round() as u16
+}
+
+impl ColorSpace for Rgb {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        Ok((
+            srgb8_to_linear(self.r),
+            srgb8_to_linear(self.g),
+            srgb8_to_linear(self.b),
+        ))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Rgb {
+        let (r, g, b) = gamut_map_srgb(linear);
+        Rgb::new(linear_to_srgb8(r), linear_to_srgb8(g), linear_to_srgb8(b))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Rgb> {
+        match code {
+            Encoding::Rgb(r, g, b) => Some(Rgb::new(*r, *g, *b)),
+            _ => None,
         }
     }
+}
 
-    #[test]
-    fn test_get_rgb() {
-        let tests: HashMap<Encoding, Rgb> = HashMap::from([
-            (Encoding::Hsl(0, 531, 582), Rgb::new(205, 92, 92)),
-            (
-                Encoding::Name(String::from("Indian Red")),
-                Rgb::new(205, 92, 92),
-            ),
-            (Encoding::Hsb(0, 551, 804), Rgb::new(205, 92, 92)),
-        ]);
+impl ColorSpace for Hex {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let h = self.h;
+        Rgb::new((h >> 16) as u8, (h >> 8) as u8, h as u8).to_linear_srgb()
+    }
 
-        for (encoding, rgb) in tests {
-            println!("input: {:?}, desired result: {:?}", encoding, rgb);
-            let result = match encoding.get_rgb() {
-                Ok(rgb) => rgb,
-                Err(e) => panic!("{e}"),
-            };
-            assert_eq!(result, rgb);
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Hex {
+        let rgb = Rgb::from_linear_srgb(linear);
+        Hex::new((rgb.r as u32) << 16 | (rgb.g as u32) << 8 | rgb.b as u32)
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Hex> {
+        match code {
+            Encoding::Hex(h) => Some(Hex::new(*h)),
+            _ => None,
         }
     }
 
-    #[test]
-    fn test_hsb() {
-        let tests: HashMap<Encoding, Hsb> = HashMap::from([
-            (Encoding::Rgb(205, 92, 92), Hsb::new(0, 551, 804)),
-            (Encoding::Hsl(0, 531, 582), Hsb::new(0, 551, 804)),
-            (
-                Encoding::Name(String::from("Indian Red")),
-                Hsb::new(0, 551, 804),
-            ),
-        ]);
+    fn validate(&self) -> Result<(), PaletteError> {
+        Hex::try_new(self.h).map(|_| ())
+    }
+}
 
-        for (encoding, hsb) in tests {
-            println!("input: {:?}, desired result: {:?}", encoding, hsb);
-            let result = match encoding.get_hsb() {
-                Ok(hsb) => hsb,
-                Err(e) => panic!("{e}"),
-            };
-            assert_eq!(result, hsb);
+impl ColorSpace for Hsl {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        Ok(srgb_to_linear(hsl_to_srgb(
+            self.h as f32,
+            self.s as f32 / 1000.0,
+            self.l as f32 / 1000.0,
+        )))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Hsl {
+        let (r, g, b) = display_srgb(linear);
+        let (h, s, l) = srgb_to_hsl(r, g, b);
+        Hsl::new(hue(h), per_mille(s), per_mille(l))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Hsl> {
+        match code {
+            Encoding::Hsl(h, s, l) => Some(Hsl::new(*h, *s, *l)),
+            _ => None,
         }
     }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Hsl::try_new(self.h, self.s, self.l).map(|_| ())
+    }
+}
+
+impl ColorSpace for Hsb {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        Ok(srgb_to_linear(hsb_to_srgb(
+            self.h as f32,
+            self.s as f32 / 1000.0,
+            self.b as f32 / 1000.0,
+        )))
+    }
+
+    // from the 8 bit rgb the color is shown as, like hsb has always been
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Hsb {
+        let rgb = Rgb::from_linear_srgb(linear);
+        let channel = |c: u8| c as f32 / 255.0;
+        let (h, s, v) = srgb_to_hsb(channel(rgb.r), channel(rgb.g), channel(rgb.b));
+        Hsb::new(hue(h), per_mille(s), per_mille(v))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Hsb> {
+        match code {
+            Encoding::Hsb(h, s, b) => Some(Hsb::new(*h, *s, *b)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Hsb::try_new(self.h, self.s, self.b).map(|_| ())
+    }
+}
+
+impl ColorSpace for Cmyk {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let k = self.k as f32 / 1000.0;
+        let channel = |ink: u16| (1.0 - ink as f32 / 1000.0) * (1.0 - k);
+        Ok(srgb_to_linear((
+            channel(self.c),
+            channel(self.m),
+            channel(self.y),
+        )))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Cmyk {
+        let (r, g, b) = display_srgb(linear);
+        let (c, m, y, k) = srgb_to_cmyk(r, g, b);
+        Cmyk::new(per_mille(c), per_mille(m), per_mille(y), per_mille(k))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Cmyk> {
+        match code {
+            Encoding::Cmyk(c, m, y, k) => Some(Cmyk::new(*c, *m, *y, *k)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Cmyk::try_new(self.c, self.m, self.y, self.k).map(|_| ())
+    }
+}
+
+impl ColorSpace for Oklab {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        Ok(oklab_to_linear_srgb(
+            self.l as f32 / 1000.0,
+            self.a as f32 / 1000.0,
+            self.b as f32 / 1000.0,
+        ))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> Oklab {
+        let (l, a, b) = linear_srgb_to_oklab(r, g, b);
+        Oklab::new(
+            (l * 1000.0).round() as u16,
+            (a * 1000.0).round() as i16,
+            (b * 1000.0).round() as i16,
+        )
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Oklab> {
+        match code {
+            Encoding::Oklab(l, a, b) => Some(Oklab::new(*l, *a, *b)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Oklab::try_new(self.l, self.a, self.b).map(|_| ())
+    }
+}
+
+impl ColorSpace for Oklch {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        // straight to linear, going through the stored oklab would round a and b
+        let c = self.c as f32 / 1000.0;
+        let h = (self.h as f32).to_radians();
+        Ok(oklab_to_linear_srgb(
+            self.l as f32 / 1000.0,
+            c * h.cos(),
+            c * h.sin(),
+        ))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> Oklch {
+        let (l, a, b) = linear_srgb_to_oklab(r, g, b);
+        let (c, h) = cartesian_to_polar(a * 1000.0, b * 1000.0);
+        Oklch::new((l * 1000.0).round() as u16, c, h)
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Oklch> {
+        match code {
+            Encoding::Oklch(l, c, h) => Some(Oklch::new(*l, *c, *h)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Oklch::try_new(self.l, self.c, self.h).map(|_| ())
+    }
+}
+
+// relative to D65 when made from the hub, get_xyz adapts to other whites
+impl ColorSpace for Xyz {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        let xyz = (
+            self.x as f32 / 1000.0,
+            self.y as f32 / 1000.0,
+            self.z as f32 / 1000.0,
+        );
+        let (x, y, z) = bradford_adapt(xyz, self.white, WhitePoint::D65);
+        Ok(xyz_d65_to_linear_srgb(x, y, z))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> Xyz {
+        let (x, y, z) = linear_srgb_to_xyz_d65(r, g, b);
+        Xyz::new(
+            WhitePoint::D65,
+            (x * 1000.0).round() as u16,
+            (y * 1000.0).round() as u16,
+            (z * 1000.0).round() as u16,
+        )
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Xyz> {
+        match code {
+            Encoding::Xyz(w, x, y, z) => Some(Xyz::new(*w, *x, *y, *z)),
+            _ => None,
+        }
+    }
+}
+
+impl Lab {
+    fn components(&self) -> (f32, f32, f32) {
+        (
+            self.l as f32 / 10.0,
+            self.a as f32 / 10.0,
+            self.b as f32 / 10.0,
+        )
+    }
+
+    fn from_components(l: f32, a: f32, b: f32) -> Lab {
+        Lab::new(
+            (l * 10.0).round() as u16,
+            (a * 10.0).round() as i16,
+            (b * 10.0).round() as i16,
+        )
+    }
+}
+
+impl ColorSpace for Lab {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (l, a, b) = self.components();
+        Ok(lab_to_linear_srgb(l, a, b))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Lab {
+        let (l, a, b) = linear_srgb_to_lab(linear);
+        Lab::from_components(l, a, b)
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Lab> {
+        match code {
+            Encoding::Lab(l, a, b) => Some(Lab::new(*l, *a, *b)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Lab::try_new(self.l, self.a, self.b).map(|_| ())
+    }
+}
+
+impl Lch {
+    fn lab_components(&self) -> (f32, f32, f32) {
+        let c = self.c as f32 / 10.0;
+        let h = (self.h as f32).to_radians();
+        (self.l as f32 / 10.0, c * h.cos(), c * h.sin())
+    }
+
+    fn from_lab_components(l: f32, a: f32, b: f32) -> Lch {
+        let c = (a * a + b * b).sqrt();
+        // hue is meaningless for greys, keep it at 0 like hsl does
+        let h = if (c * 10.0).round() == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        Lch::new(
+            (l * 10.0).round() as u16,
+            (c * 10.0).round() as u16,
+            (h.round() as u16) % 360,
+        )
+    }
+}
+
+impl ColorSpace for Lch {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (l, a, b) = self.lab_components();
+        Ok(lab_to_linear_srgb(l, a, b))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Lch {
+        let (l, a, b) = linear_srgb_to_lab(linear);
+        Lch::from_lab_components(l, a, b)
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Lch> {
+        match code {
+            Encoding::Lch(l, c, h) => Some(Lch::new(*l, *c, *h)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Lch::try_new(self.l, self.c, self.h).map(|_| ())
+    }
+}
+
+impl ColorSpace for DisplayP3 {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (x, y, z) = display_p3_to_xyz_d65(
+            srgb_transfer_inverse(self.r as f32 / 1000.0),
+            srgb_transfer_inverse(self.g as f32 / 1000.0),
+            srgb_transfer_inverse(self.b as f32 / 1000.0),
+        );
+        Ok(xyz_d65_to_linear_srgb(x, y, z))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> DisplayP3 {
+        let (x, y, z) = linear_srgb_to_xyz_d65(r, g, b);
+        let (r, g, b) = xyz_d65_to_display_p3(x, y, z);
+        let channel = |c: f32| (srgb_transfer(c.clamp(0.0, 1.0)) * 1000.0).round() as u16;
+        DisplayP3::new(channel(r), channel(g), channel(b))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<DisplayP3> {
+        match code {
+            Encoding::DisplayP3(r, g, b) => Some(DisplayP3::new(*r, *g, *b)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        DisplayP3::try_new(self.r, self.g, self.b).map(|_| ())
+    }
+}
+
+impl ColorSpace for Rec2020 {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (x, y, z) = rec2020_to_xyz_d65(
+            rec2020_transfer_inverse(self.r as f32 / 1000.0),
+            rec2020_transfer_inverse(self.g as f32 / 1000.0),
+            rec2020_transfer_inverse(self.b as f32 / 1000.0),
+        );
+        Ok(xyz_d65_to_linear_srgb(x, y, z))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> Rec2020 {
+        let (x, y, z) = linear_srgb_to_xyz_d65(r, g, b);
+        let (r, g, b) = xyz_d65_to_rec2020(x, y, z);
+        let channel = |c: f32| (rec2020_transfer(c.clamp(0.0, 1.0)) * 1000.0).round() as u16;
+        Rec2020::new(channel(r), channel(g), channel(b))
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Rec2020> {
+        match code {
+            Encoding::Rec2020(r, g, b) => Some(Rec2020::new(*r, *g, *b)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Rec2020::try_new(self.r, self.g, self.b).map(|_| ())
+    }
+}
+
+impl ColorSpace for Hsluv {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (h, s, l) = (self.h as f32, self.s as f32 / 10.0, self.l as f32 / 10.0);
+        let c = if (LUV_BLACK..=LUV_WHITE).contains(&l) {
+            max_chroma_for_lh(l, h) * s / 100.0
+        } else {
+            0.0
+        };
+        Ok(lchuv_to_linear_srgb(l.clamp(0.0, 100.0), c, h))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Hsluv {
+        let (l, c, h) = linear_srgb_to_lchuv(linear);
+        let s = if (LUV_BLACK..=LUV_WHITE).contains(&l) {
+            (c / max_chroma_for_lh(l, h) * 100.0).min(100.0)
+        } else {
+            0.0
+        };
+        Hsluv::new(
+            h.round() as u16 % 360,
+            (s * 10.0).round() as u16,
+            (l * 10.0).round() as u16,
+        )
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Hsluv> {
+        match code {
+            Encoding::Hsluv(h, s, l) => Some(Hsluv::new(*h, *s, *l)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Hsluv::try_new(self.h, self.s, self.l).map(|_| ())
+    }
+}
+
+// McCamy's approximation, good to a few kelvin along the blackbody locus. Only the
+// chromaticity is kept, going back gives the brightest color of that temperature
+impl ColorSpace for Kelvin {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (x, y, z) = kelvin_to_xyz(self.k as f32);
+        let (r, g, b) = xyz_d65_to_linear_srgb(x, y, z);
+        let max = r.max(g).max(b);
+        Ok((r / max, g / max, b / max))
+    }
+
+    fn from_linear_srgb((r, g, b): (f32, f32, f32)) -> Kelvin {
+        let (x, y, z) = linear_srgb_to_xyz_d65(r, g, b);
+        let sum = x + y + z;
+        // black has no chromaticity, call it the white point
+        let (x, y) = if sum <= 0.0 {
+            (0.3127, 0.3290)
+        } else {
+            (x / sum, y / sum)
+        };
+        let n = (x - 0.3320) / (0.1858 - y);
+        let cct = 449.0 * n.powi(3) + 3525.0 * n.powi(2) + 6823.3 * n + 5520.33;
+        Kelvin::new(cct.clamp(KELVIN_MIN, KELVIN_MAX).round() as u16)
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Kelvin> {
+        match code {
+            Encoding::Kelvin(k) => Some(Kelvin::new(*k)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Kelvin::try_new(self.k).map(|_| ())
+    }
+}
+
+// nearest of the color cube and the gray ramp, the first 16 are left out since
+// terminal themes are free to redefine them
+impl ColorSpace for Ansi256 {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        let (r, g, b) = ansi256_to_rgb(self.i);
+        Rgb::new(r, g, b).to_linear_srgb()
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Ansi256 {
+        let rgb = Rgb::from_linear_srgb(linear);
+        let level = |c: u8| {
+            ANSI_CUBE_LEVELS
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, level)| (c as i32 - **level as i32).abs())
+                .map_or(0, |(i, _)| i as u8)
+        };
+        let cube = 16 + 36 * level(rgb.r) + 6 * level(rgb.g) + level(rgb.b);
+
+        let average = (rgb.r as u32 + rgb.g as u32 + rgb.b as u32) / 3;
+        let gray = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+        let oklab = |rgb: &Rgb| {
+            Oklab::from_linear_srgb((
+                srgb8_to_linear(rgb.r),
+                srgb8_to_linear(rgb.g),
+                srgb8_to_linear(rgb.b),
+            ))
+        };
+        let distance = |i: u8| {
+            let (r, g, b) = ansi256_to_rgb(i);
+            ok_distance(&oklab(&rgb), &oklab(&Rgb::new(r, g, b)))
+        };
+        if distance(gray) < distance(cube) {
+            Ansi256::new(gray)
+        } else {
+            Ansi256::new(cube)
+        }
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Ansi256> {
+        match code {
+            Encoding::Ansi256(i) => Some(Ansi256::new(*i)),
+            _ => None,
+        }
+    }
+}
+
+// unlike hsluv saturation can pass 1000, those colors are outside srgb
+impl ColorSpace for Hpluv {
+    fn to_linear_srgb(&self) -> Result<(f32, f32, f32), PaletteError> {
+        self.validate()?;
+        let (h, s, l) = (self.h as f32, self.s as f32 / 10.0, self.l as f32 / 10.0);
+        let c = if (LUV_BLACK..=LUV_WHITE).contains(&l) {
+            max_safe_chroma_for_l(l) * s / 100.0
+        } else {
+            0.0
+        };
+        Ok(lchuv_to_linear_srgb(l.clamp(0.0, 100.0), c, h))
+    }
+
+    fn from_linear_srgb(linear: (f32, f32, f32)) -> Hpluv {
+        let (l, c, h) = linear_srgb_to_lchuv(linear);
+        let s = if (LUV_BLACK..=LUV_WHITE).contains(&l) {
+            c / max_safe_chroma_for_l(l) * 100.0
+        } else {
+            0.0
+        };
+        Hpluv::new(
+            h.round() as u16 % 360,
+            (s * 10.0).round() as u16,
+            (l * 10.0).round() as u16,
+        )
+    }
+
+    fn from_encoding(code: &Encoding) -> Option<Hpluv> {
+        match code {
+            Encoding::Hpluv(h, s, l) => Some(Hpluv::new(*h, *s, *l)),
+            _ => None,
+        }
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        Hpluv::try_new(self.h, self.s, self.l).map(|_| ())
+    }
+}
+
+// -----------------------
+
+fn srgb_transfer_inverse(c: f32) -> f32 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn srgb_transfer(c: f32) -> f32 {
+    if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub(crate) fn srgb8_to_linear(c: u8) -> f32 {
+    srgb_transfer_inverse(c as f32 / 255.0)
+}
+
+pub(crate) fn linear_to_srgb8(c: f32) -> u8 {
+    (srgb_transfer(c.clamp(0.0, 1.0)) * 255.0).round() as u8
+}
+
+const REC2020_ALPHA: f32 = 1.099_296_8;
+const REC2020_BETA: f32 = 0.018_053_97;
+
+fn rec2020_transfer_inverse(c: f32) -> f32 {
+    if c < REC2020_BETA * 4.5 {
+        c / 4.5
+    } else {
+        ((c + REC2020_ALPHA - 1.0) / REC2020_ALPHA).powf(1.0 / 0.45)
+    }
+}
+
+fn rec2020_transfer(c: f32) -> f32 {
+    if c < REC2020_BETA {
+        c * 4.5
+    } else {
+        REC2020_ALPHA * c.powf(0.45) - (REC2020_ALPHA - 1.0)
+    }
+}
+
+fn display_p3_to_xyz_d65(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    (
+        0.486_570_9 * r + 0.265_667_7 * g + 0.198_217_3 * b,
+        0.228_974_6 * r + 0.691_738_5 * g + 0.079_286_9 * b,
+        0.045_113_4 * g + 1.043_944_4 * b,
+    )
+}
+
+fn xyz_d65_to_display_p3(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    (
+        2.493_497 * x - 0.931_383_6 * y - 0.402_710_8 * z,
+        -0.829_489 * x + 1.762_664_1 * y + 0.023_624_7 * z,
+        0.035_845_8 * x - 0.076_172_4 * y + 0.956_884_5 * z,
+    )
+}
+
+fn rec2020_to_xyz_d65(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    (
+        0.636_958 * r + 0.144_616_9 * g + 0.168_881 * b,
+        0.262_700_2 * r + 0.677_998_1 * g + 0.059_301_7 * b,
+        0.028_072_7 * g + 1.060_985_1 * b,
+    )
+}
+
+fn xyz_d65_to_rec2020(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    (
+        1.716_651_2 * x - 0.355_670_8 * y - 0.253_366_3 * z,
+        -0.666_684_4 * x + 1.616_481_2 * y + 0.015_768_5 * z,
+        0.017_639_9 * x - 0.042_770_6 * y + 0.942_103_1 * z,
+    )
+}
+
+// -----------------------
+
+// past these lightnesses every hue collapses to black or white
+const LUV_BLACK: f32 = 0.00001;
+const LUV_WHITE: f32 = 99.9999;
+
+fn xyz_d65_to_lchuv(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    let (xw, yw, zw) = WhitePoint::D65.xyz();
+    let l = if y > LAB_EPSILON {
+        116.0 * y.cbrt() - 16.0
+    } else {
+        LAB_KAPPA * y
+    };
+    let denominator = x + 15.0 * y + 3.0 * z;
+    if l <= 0.0 || denominator == 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let white_denominator = xw + 15.0 * yw + 3.0 * zw;
+    let u = 13.0 * l * (4.0 * x / denominator - 4.0 * xw / white_denominator);
+    let v = 13.0 * l * (9.0 * y / denominator - 9.0 * yw / white_denominator);
+
+    let c = (u * u + v * v).sqrt();
+    let h = if c < 1e-4 {
+        0.0
+    } else {
+        v.atan2(u).to_degrees().rem_euclid(360.0)
+    };
+    (l, c, h)
+}
+
+fn lchuv_to_xyz_d65(l: f32, c: f32, h: f32) -> (f32, f32, f32) {
+    if l <= 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+    let (xw, yw, zw) = WhitePoint::D65.xyz();
+    let white_denominator = xw + 15.0 * yw + 3.0 * zw;
+    let (u, v) = (c * h.to_radians().cos(), c * h.to_radians().sin());
+    let u_prime = u / (13.0 * l) + 4.0 * xw / white_denominator;
+    let v_prime = v / (13.0 * l) + 9.0 * yw / white_denominator;
+
+    let y = if l > 8.0 {
+        ((l + 16.0) / 116.0).powi(3)
+    } else {
+        l / LAB_KAPPA
+    };
+    let x = y * 9.0 * u_prime / (4.0 * v_prime);
+    let z = y * (12.0 - 3.0 * u_prime - 20.0 * v_prime) / (4.0 * v_prime);
+    (x, y, z)
+}
+
+fn linear_srgb_to_lchuv((r, g, b): (f32, f32, f32)) -> (f32, f32, f32) {
+    let (x, y, z) = linear_srgb_to_xyz_d65(r, g, b);
+    xyz_d65_to_lchuv(x, y, z)
+}
+
+fn lchuv_to_linear_srgb(l: f32, c: f32, h: f32) -> (f32, f32, f32) {
+    let (x, y, z) = lchuv_to_xyz_d65(l, c, h);
+    xyz_d65_to_linear_srgb(x, y, z)
+}
+
+const XYZ_D65_TO_LINEAR_SRGB: [[f32; 3]; 3] = [
+    [3.240_454_2, -1.537_138_5, -0.498_531_4],
+    [-0.969_266, 1.876_010_8, 0.041_556],
+    [0.055_643_4, -0.204_025_9, 1.057_225_2],
+];
+
+// the six lines in the uv plane where an srgb channel hits 0 or 1 at lightness l,
+// as (slope, intercept)
+fn luv_gamut_bounds(l: f32) -> [(f32, f32); 6] {
+    let sub1 = (l + 16.0).powi(3) / 1_560_896.0;
+    let sub2 = if sub1 > LAB_EPSILON {
+        sub1
+    } else {
+        l / LAB_KAPPA
+    };
+
+    let mut bounds = [(0.0, 0.0); 6];
+    for (channel, [m1, m2, m3]) in XYZ_D65_TO_LINEAR_SRGB.iter().enumerate() {
+        for t in 0..2 {
+            let top1 = (284_517.0 * m1 - 94_839.0 * m3) * sub2;
+            let top2 = (838_422.0 * m3 + 769_860.0 * m2 + 731_718.0 * m1) * l * sub2
+                - 769_860.0 * t as f32 * l;
+            let bottom = (632_260.0 * m3 - 126_452.0 * m2) * sub2 + 126_452.0 * t as f32;
+            bounds[channel * 2 + t] = (top1 / bottom, top2 / bottom);
+        }
+    }
+    bounds
+}
+
+// the most chroma srgb can show at this lightness and hue
+fn max_chroma_for_lh(l: f32, h: f32) -> f32 {
+    let h = h.to_radians();
+    luv_gamut_bounds(l)
+        .iter()
+        .map(|(slope, intercept)| intercept / (h.sin() - slope * h.cos()))
+        .filter(|length| *length >= 0.0)
+        .fold(f32::MAX, f32::min)
+}
+
+// the most chroma srgb can show at this lightness for every hue
+fn max_safe_chroma_for_l(l: f32) -> f32 {
+    luv_gamut_bounds(l)
+        .iter()
+        .map(|(slope, intercept)| intercept.abs() / (slope * slope + 1.0).sqrt())
+        .fold(f32::MAX, f32::min)
+}
+
+// -----------------------
+
+// xterm's defaults, terminals often theme these
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+const ANSI_CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn ansi256_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..=15 => ANSI_16[i as usize],
+        16..=231 => {
+            let i = i - 16;
+            (
+                ANSI_CUBE_LEVELS[(i / 36) as usize],
+                ANSI_CUBE_LEVELS[(i / 6 % 6) as usize],
+                ANSI_CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let level = 8 + 10 * (i - 232);
+            (level, level, level)
+        }
+    }
+}
+
+// -----------------------
+
+// hsl, hsb and cmyk work on gamma encoded srgb in 0..1, hue in degrees
+
+fn hue_to_srgb(h: f32, c: f32, m: f32) -> (f32, f32, f32) {
+    let x = c * (1.0 - ((h / 60.0) % 2.0 - 1.0).abs());
+    let (r, g, b) = match (h / 60.0) as u16 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    (r + m, g + m, b + m)
+}
+
+fn srgb_to_hue(r: f32, g: f32, b: f32) -> f32 {
+    let max = r.max(g).max(b);
+    let delta = max - r.min(g).min(b);
+    if delta <= 0.0 {
+        return 0.0;
+    }
+    let h = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    h * 60.0
+}
+
+fn hsl_to_srgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    hue_to_srgb(h, c, l - c / 2.0)
+}
+
+fn srgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let s = if max == min {
+        0.0
+    } else {
+        (max - min) / (1.0 - (2.0 * l - 1.0).abs())
+    };
+    (srgb_to_hue(r, g, b), s, l)
+}
+
+fn hsb_to_srgb(h: f32, s: f32, b: f32) -> (f32, f32, f32) {
+    let c = b * s;
+    hue_to_srgb(h, c, b - c)
+}
+
+fn srgb_to_hsb(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let max = r.max(g).max(b);
+    let s = if max <= 0.0 {
+        0.0
+    } else {
+        (max - r.min(g).min(b)) / max
+    };
+    (srgb_to_hue(r, g, b), s, max)
+}
+
+fn srgb_to_cmyk(r: f32, g: f32, b: f32) -> (f32, f32, f32, f32) {
+    let k = 1.0 - r.max(g).max(b);
+    if k >= 1.0 {
+        return (0.0, 0.0, 0.0, 1.0);
+    }
+    (
+        (1.0 - r - k) / (1.0 - k),
+        (1.0 - g - k) / (1.0 - k),
+        (1.0 - b - k) / (1.0 - k),
+        k,
+    )
+}
+
+// -----------------------
+
+// range of the planckian locus fit below
+pub(crate) const KELVIN_MIN: f32 = 1667.0;
+pub(crate) const KELVIN_MAX: f32 = 25000.0;
+
+/// XYZ of a blackbody radiator with Y = 1, from Kim et al.'s cubic fit of the planckian
+/// locus. Temperatures are clamped to the range of the fit.
+pub(crate) fn kelvin_to_xyz(kelvin: f32) -> (f32, f32, f32) {
+    let t = kelvin.clamp(KELVIN_MIN, KELVIN_MAX);
+    let x = if t <= 4000.0 {
+        -0.266_123_9e9 / t.powi(3) - 0.234_358_9e6 / t.powi(2) + 0.877_695_6e3 / t + 0.179_910
+    } else {
+        -3.025_846_9e9 / t.powi(3) + 2.107_038e6 / t.powi(2) + 0.222_634_7e3 / t + 0.240_390
+    };
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x.powi(3) - 1.348_110_2 * x.powi(2) + 2.185_558_3 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x.powi(3) - 1.374_185_9 * x.powi(2) + 2.091_37 * x - 0.167_488_67
+    } else {
+        3.081_758 * x.powi(3) - 5.873_387 * x.powi(2) + 3.751_13 * x - 0.370_014_83
+    };
+    (x / y, 1.0, (1.0 - x - y) / y)
+}
+
+// -----------------------
+
+const GAMUT_TOLERANCE: f32 = 0.002;
+// deltaEOK below which clipping is considered invisible
+const GAMUT_JND: f32 = 0.02;
+const GAMUT_EPSILON: f32 = 0.0001;
+
+pub(crate) fn linear_in_srgb_gamut(rgb: (f32, f32, f32)) -> bool {
+    in_unit_cube(rgb, GAMUT_TOLERANCE)
+}
+
+fn in_unit_cube((r, g, b): (f32, f32, f32), tolerance: f32) -> bool {
+    [r, g, b]
+        .iter()
+        .all(|c| (-tolerance..=1.0 + tolerance).contains(c))
+}
+
+fn clip((r, g, b): (f32, f32, f32)) -> (f32, f32, f32) {
+    (r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0))
+}
+
+fn delta_e_ok(rgb1: (f32, f32, f32), rgb2: (f32, f32, f32)) -> f32 {
+    let (l1, a1, b1) = linear_srgb_to_oklab(rgb1.0, rgb1.1, rgb1.2);
+    let (l2, a2, b2) = linear_srgb_to_oklab(rgb2.0, rgb2.1, rgb2.2);
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2)).sqrt()
+}
+
+/// CSS Color 4 gamut mapping: lower OKLCH chroma at constant lightness and hue
+/// until clipping the result is no longer noticeable.
+fn gamut_map_srgb(rgb: (f32, f32, f32)) -> (f32, f32, f32) {
+    if in_unit_cube(rgb, GAMUT_TOLERANCE) {
+        return clip(rgb);
+    }
+
+    let (l, a, b) = linear_srgb_to_oklab(rgb.0, rgb.1, rgb.2);
+    if l >= 1.0 {
+        return (1.0, 1.0, 1.0);
+    }
+    if l <= 0.0 {
+        return (0.0, 0.0, 0.0);
+    }
+
+    let mut clipped = clip(rgb);
+    if delta_e_ok(clipped, rgb) < GAMUT_JND {
+        return clipped;
+    }
+
+    let hue = b.atan2(a);
+    let with_chroma = |chroma: f32| oklab_to_linear_srgb(l, chroma * hue.cos(), chroma * hue.sin());
+
+    let mut min = 0.0;
+    let mut max = (a * a + b * b).sqrt();
+    let mut min_in_gamut = true;
+    while max - min > GAMUT_EPSILON {
+        let chroma = (min + max) / 2.0;
+        let current = with_chroma(chroma);
+        if min_in_gamut && in_unit_cube(current, 0.0) {
+            min = chroma;
+            continue;
+        }
+        clipped = clip(current);
+        let error = delta_e_ok(clipped, current);
+        if error < GAMUT_JND {
+            if GAMUT_JND - error < GAMUT_EPSILON {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        } else {
+            max = chroma;
+        }
+    }
+
+    clipped
+}
+
+pub(crate) fn linear_srgb_to_oklab(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    (
+        0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+        1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+        0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+    )
+}
+
+fn oklab_to_linear_srgb(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let l_ = (l + 0.396_337_78 * a + 0.215_803_76 * b).powi(3);
+    let m_ = (l - 0.105_561_346 * a - 0.063_854_17 * b).powi(3);
+    let s_ = (l - 0.089_484_18 * a - 1.291_485_5 * b).powi(3);
+
+    (
+        4.076_741_7 * l_ - 3.307_711_6 * m_ + 0.230_969_94 * s_,
+        -1.268_438 * l_ + 2.609_757_4 * m_ - 0.341_319_38 * s_,
+        -0.004_196_086_3 * l_ - 0.703_418_6 * m_ + 1.707_614_7 * s_,
+    )
+}
+
+// hue is meaningless for greys, keep it at 0 like hsl does
+fn cartesian_to_polar(a: f32, b: f32) -> (u16, u16) {
+    let c = (a * a + b * b).sqrt();
+    let h = if c.round() == 0.0 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    (c.round() as u16, (h.round() as u16) % 360)
+}
+
+fn linear_srgb_to_xyz_d65(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+    (
+        0.412_456_4 * r + 0.357_576_1 * g + 0.180_437_5 * b,
+        0.212_672_9 * r + 0.715_152_2 * g + 0.072_175 * b,
+        0.019_333_9 * r + 0.119_192 * g + 0.950_304_1 * b,
+    )
+}
+
+fn xyz_d65_to_linear_srgb(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    (
+        3.240_454_2 * x - 1.537_138_5 * y - 0.498_531_4 * z,
+        -0.969_266 * x + 1.876_010_8 * y + 0.041_556 * z,
+        0.055_643_4 * x - 0.204_025_9 * y + 1.057_225_2 * z,
+    )
+}
+
+/// Bradford chromatic adaptation between reference whites.
+pub(crate) fn bradford_adapt(
+    xyz: (f32, f32, f32),
+    from: WhitePoint,
+    to: WhitePoint,
+) -> (f32, f32, f32) {
+    if from == to {
+        return xyz;
+    }
+    bradford_adapt_xyz(xyz, from.xyz(), to.xyz())
+}
+
+fn bradford_adapt_xyz(
+    xyz: (f32, f32, f32),
+    from: (f32, f32, f32),
+    to: (f32, f32, f32),
+) -> (f32, f32, f32) {
+    let to_cone = |(x, y, z): (f32, f32, f32)| {
+        (
+            0.8951 * x + 0.2664 * y - 0.1614 * z,
+            -0.7502 * x + 1.7135 * y + 0.0367 * z,
+            0.0389 * x - 0.0685 * y + 1.0296 * z,
+        )
+    };
+
+    let (rho, gamma, beta) = to_cone(xyz);
+    let (rho_src, gamma_src, beta_src) = to_cone(from);
+    let (rho_dst, gamma_dst, beta_dst) = to_cone(to);
+
+    let rho = rho * rho_dst / rho_src;
+    let gamma = gamma * gamma_dst / gamma_src;
+    let beta = beta * beta_dst / beta_src;
+
+    (
+        0.986_992_9 * rho - 0.147_054_3 * gamma + 0.159_962_7 * beta,
+        0.432_305_3 * rho + 0.518_360_3 * gamma + 0.049_291_2 * beta,
+        -0.008_528_7 * rho + 0.040_042_8 * gamma + 0.968_486_7 * beta,
+    )
+}
+
+const LAB_EPSILON: f32 = 216.0 / 24389.0;
+const LAB_KAPPA: f32 = 24389.0 / 27.0;
+
+fn xyz_d50_to_lab(x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+    let (xw, yw, zw) = WhitePoint::D50.xyz();
+    let f = |t: f32| {
+        if t > LAB_EPSILON {
+            t.cbrt()
+        } else {
+            (LAB_KAPPA * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x / xw), f(y / yw), f(z / zw));
+
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+fn lab_to_xyz_d50(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let (xw, yw, zw) = WhitePoint::D50.xyz();
+    let fy = (l + 16.0) / 116.0;
+    let fx = fy + a / 500.0;
+    let fz = fy - b / 200.0;
+    let f_inv = |t: f32| {
+        if t.powi(3) > LAB_EPSILON {
+            t.powi(3)
+        } else {
+            (116.0 * t - 16.0) / LAB_KAPPA
+        }
+    };
+    let y = if l > LAB_KAPPA * LAB_EPSILON {
+        fy.powi(3)
+    } else {
+        l / LAB_KAPPA
+    };
+
+    (f_inv(fx) * xw, y * yw, f_inv(fz) * zw)
+}
+
+fn linear_srgb_to_lab((r, g, b): (f32, f32, f32)) -> (f32, f32, f32) {
+    let xyz = linear_srgb_to_xyz_d65(r, g, b);
+    let (x, y, z) = bradford_adapt(xyz, WhitePoint::D65, WhitePoint::D50);
+    xyz_d50_to_lab(x, y, z)
+}
+
+fn lab_to_linear_srgb(l: f32, a: f32, b: f32) -> (f32, f32, f32) {
+    let xyz = lab_to_xyz_d50(l, a, b);
+    let (x, y, z) = bradford_adapt(xyz, WhitePoint::D50, WhitePoint::D65);
+    xyz_d65_to_linear_srgb(x, y, z)
+}
+
+// -----------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    // use crate::color_spaces::*;
+
+    #[test]
+    fn test_get_name() {
+        let tests: HashMap<Encoding, String> = HashMap::from([
+            (Encoding::Rgb(205, 92, 92), String::from("Indian Red")),
+            (Encoding::Rgb(205, 91, 93), String::from("Indian Red")),
+            // don't know how it translates, so can't test off by ones
+            (Encoding::Hsl(0, 531, 582), String::from("Indian Red")),
+            (Encoding::Hsb(0, 551, 804), String::from("Indian Red")),
+        ]);
+
+        for (encoding, name) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, name);
+            let result = match encoding.get_name() {
+                Ok(name) => name,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, name);
+        }
+    }
+
+    #[test]
+    fn test_get_rgb() {
+        let tests: HashMap<Encoding, Rgb> = HashMap::from([
+            (Encoding::Hsl(0, 531, 582), Rgb::new(205, 92, 92)),
+            (
+                Encoding::Name(String::from("Indian Red")),
+                Rgb::new(205, 92, 92),
+            ),
+            (Encoding::Hsb(0, 551, 804), Rgb::new(205, 92, 92)),
+        ]);
+
+        for (encoding, rgb) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, rgb);
+            let result = match encoding.get_rgb() {
+                Ok(rgb) => rgb,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, rgb);
+        }
+    }
+
+    #[test]
+    fn test_hsb() {
+        let tests: HashMap<Encoding, Hsb> = HashMap::from([
+            (Encoding::Rgb(205, 92, 92), Hsb::new(0, 551, 804)),
+            (Encoding::Hsl(0, 531, 582), Hsb::new(0, 551, 804)),
+            (
+                Encoding::Name(String::from("Indian Red")),
+                Hsb::new(0, 551, 804),
+            ),
+        ]);
+
+        for (encoding, hsb) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, hsb);
+            let result = match encoding.get_hsb() {
+                Ok(hsb) => hsb,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, hsb);
+        }
+    }
+
+    #[test]
+    fn hsb_from_hsl_matches_its_rgb() {
+        // colors are shown as 8 bit rgb, hsb should describe that color
+        let tests = [
+            Encoding::Hsl(0, 531, 582),
+            Encoding::Hsl(120, 1000, 250),
+            Encoding::Hsl(207, 440, 490),
+            Encoding::Hsl(300, 333, 900),
+            Encoding::Hsl(60, 0, 500),
+        ];
+
+        for hsl in tests {
+            let rgb = match hsl.get_rgb() {
+                Ok(rgb) => rgb,
+                Err(e) => panic!("{e}"),
+            };
+            let expected = Encoding::Rgb(rgb.r, rgb.g, rgb.b).get_hsb().unwrap();
+            println!("input: {:?}, desired result: {:?}", hsl, expected);
+            assert_eq!(hsl.get_hsb().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_oklab() {
+        let tests: HashMap<Encoding, Oklab> = HashMap::from([
+            (Encoding::Rgb(255, 255, 255), Oklab::new(1000, 0, 0)),
+            (Encoding::Rgb(0, 0, 0), Oklab::new(0, 0, 0)),
+            (Encoding::Rgb(255, 0, 0), Oklab::new(628, 225, 126)),
+            (Encoding::Hex(0x0000FF), Oklab::new(452, -32, -312)),
+        ]);
+
+        for (encoding, oklab) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, oklab);
+            let result = match encoding.get_oklab() {
+                Ok(oklab) => oklab,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, oklab);
+        }
+    }
+
+    #[test]
+    fn test_oklch() {
+        let tests: HashMap<Encoding, Oklch> = HashMap::from([
+            (Encoding::Rgb(128, 128, 128), Oklch::new(600, 0, 0)),
+            (Encoding::Rgb(255, 0, 0), Oklch::new(628, 258, 29)),
+            (Encoding::Oklab(628, 225, 126), Oklch::new(628, 258, 29)),
+        ]);
+
+        for (encoding, oklch) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, oklch);
+            let result = match encoding.get_oklch() {
+                Ok(oklch) => oklch,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, oklch);
+        }
+    }
+
+    #[test]
+    fn oklch_is_rounded_once() {
+        // a and b aren't rounded on the way, low chromas would drift if they were
+        for oklch in [
+            Oklch::new(600, 5, 29),
+            Oklch::new(700, 37, 200),
+            Oklch::new(450, 120, 300),
+            Oklch::new(820, 3, 110),
+        ] {
+            println!("input: {:?}, desired result: the same", oklch);
+            let color = Color::new(oklch.encode()).unwrap();
+            assert_eq!(*color.oklch(), oklch);
+        }
+    }
+
+    fn assert_rgb_close(result: Rgb, expected: &Rgb, tolerance: i32) {
+        println!("result: {:?}, desired result: {:?}", result, expected);
+        assert!((result.r as i32 - expected.r as i32).abs() <= tolerance);
+        assert!((result.g as i32 - expected.g as i32).abs() <= tolerance);
+        assert!((result.b as i32 - expected.b as i32).abs() <= tolerance);
+    }
+
+    #[test]
+    fn oklab_round_trips_through_rgb() {
+        for rgb in [
+            Rgb::new(205, 92, 92),
+            Rgb::new(255, 0, 0),
+            Rgb::new(70, 130, 180),
+        ] {
+            let oklab = rgb.encode().get_oklab().unwrap();
+            assert_rgb_close(oklab.encode().get_rgb().unwrap(), &rgb, 1);
+            // whole-degree hues are coarser than a u8 channel at high chroma
+            let oklch = rgb.encode().get_oklch().unwrap();
+            assert_rgb_close(oklch.encode().get_rgb().unwrap(), &rgb, 4);
+        }
+    }
+
+    #[test]
+    fn test_xyz() {
+        let tests: HashMap<(Encoding, WhitePoint), Xyz> = HashMap::from([
+            (
+                (Encoding::Rgb(255, 255, 255), WhitePoint::D65),
+                Xyz::new(WhitePoint::D65, 950, 1000, 1089),
+            ),
+            (
+                (Encoding::Rgb(255, 255, 255), WhitePoint::D50),
+                Xyz::new(WhitePoint::D50, 964, 1000, 825),
+            ),
+            (
+                (Encoding::Rgb(255, 0, 0), WhitePoint::D65),
+                Xyz::new(WhitePoint::D65, 412, 213, 19),
+            ),
+            (
+                (
+                    Encoding::Xyz(WhitePoint::D65, 950, 1000, 1089),
+                    WhitePoint::D50,
+                ),
+                Xyz::new(WhitePoint::D50, 964, 1000, 825),
+            ),
+        ]);
+
+        for ((encoding, white), xyz) in tests {
+            println!(
+                "input: {:?} {:?}, desired result: {:?}",
+                encoding, white, xyz
+            );
+            let result = match encoding.get_xyz(white) {
+                Ok(xyz) => xyz,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, xyz);
+        }
+    }
+
+    #[test]
+    fn test_lab() {
+        let tests: HashMap<Encoding, Lab> = HashMap::from([
+            (Encoding::Rgb(255, 255, 255), Lab::new(1000, 0, 0)),
+            (Encoding::Rgb(0, 0, 0), Lab::new(0, 0, 0)),
+            (Encoding::Rgb(255, 0, 0), Lab::new(543, 808, 699)),
+            (Encoding::Lch(543, 1068, 41), Lab::new(543, 806, 701)),
+        ]);
+
+        for (encoding, lab) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, lab);
+            let result = match encoding.get_lab() {
+                Ok(lab) => lab,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, lab);
+        }
+    }
+
+    #[test]
+    fn test_lch() {
+        let tests: HashMap<Encoding, Lch> = HashMap::from([
+            (Encoding::Rgb(128, 128, 128), Lch::new(536, 0, 0)),
+            (Encoding::Rgb(255, 0, 0), Lch::new(543, 1068, 41)),
+        ]);
+
+        for (encoding, lch) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, lch);
+            let result = match encoding.get_lch() {
+                Ok(lch) => lch,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, lch);
+        }
+    }
+
+    #[test]
+    fn lab_round_trips_through_rgb() {
+        for rgb in [
+            Rgb::new(205, 92, 92),
+            Rgb::new(240, 128, 128),
+            Rgb::new(70, 130, 180),
+        ] {
+            let xyz = rgb.encode().get_xyz(WhitePoint::D50).unwrap();
+            assert_rgb_close(xyz.encode().get_rgb().unwrap(), &rgb, 1);
+            let lab = rgb.encode().get_lab().unwrap();
+            assert_rgb_close(lab.encode().get_rgb().unwrap(), &rgb, 1);
+            assert_rgb_close(
+                lab.encode().get_hsl().unwrap().encode().get_rgb().unwrap(),
+                &rgb,
+                1,
+            );
+            // whole-degree hues are coarser than a u8 channel at high chroma
+            let lch = rgb.encode().get_lch().unwrap();
+            assert_rgb_close(lch.encode().get_rgb().unwrap(), &rgb, 4);
+        }
+    }
+
+    #[test]
+    fn test_cmyk() {
+        let tests: HashMap<Encoding, Cmyk> = HashMap::from([
+            (Encoding::Rgb(255, 255, 255), Cmyk::new(0, 0, 0, 0)),
+            (Encoding::Rgb(0, 0, 0), Cmyk::new(0, 0, 0, 1000)),
+            (Encoding::Rgb(205, 92, 92), Cmyk::new(0, 551, 551, 196)),
+            (Encoding::Hex(0x0000FF), Cmyk::new(1000, 1000, 0, 0)),
+        ]);
+
+        for (encoding, cmyk) in tests {
+            println!("input: {:?}, desired result: {:?}", encoding, cmyk);
+            let result = match encoding.get_cmyk() {
+                Ok(cmyk) => cmyk,
+                Err(e) => panic!("{e}"),
+            };
+            assert_eq!(result, cmyk);
+            assert_rgb_close(
+                cmyk.encode().get_rgb().unwrap(),
+                &encoding.get_rgb().unwrap(),
+                1,
+            );
+        }
+    }
+
+    #[test]
+    fn cmyk_ink_limit_keeps_black() {
+        // 100% cyan and magenta on 90% black is 290% coverage
+        let encoding = Encoding::Cmyk(1000, 1000, 0, 900);
+        let limited = encoding.get_cmyk_with_ink_limit(2400).unwrap();
+        assert_eq!(limited, Cmyk::new(750, 750, 0, 900));
+
+        let unlimited = encoding.get_cmyk_with_ink_limit(3000).unwrap();
+        assert_eq!(unlimited, Cmyk::new(1000, 1000, 0, 900));
+    }
+
+    #[test]
+    fn alpha_carries_through_every_encoding() {
+        let encoding = Hex::new(0xCD5C5C).with_alpha(128).encode();
+        assert_eq!(
+            encoding,
+            Encoding::Alpha(Box::new(Encoding::Hex(0xCD5C5C)), 128)
+        );
+        assert_eq!(encoding.get_alpha(), 128);
+        assert_eq!(
+            encoding.get_rgb().unwrap(),
+            Rgb::new(205, 92, 92).with_alpha(128)
+        );
+        assert_eq!(
+            encoding.get_hsl().unwrap(),
+            Hsl::new(0, 531, 582).with_alpha(128)
+        );
+        assert_eq!(
+            encoding.get_hsb().unwrap(),
+            Hsb::new(0, 551, 804).with_alpha(128)
+        );
+        assert_eq!(encoding.get_name().unwrap(), "Indian Red");
+
+        // opaque colors stay bare so existing encodings compare equal
+        assert_eq!(
+            Encoding::Rgb(1, 2, 3).with_alpha(OPAQUE),
+            Encoding::Rgb(1, 2, 3)
+        );
+        assert_eq!(Rgb::new(1, 2, 3).encode(), Encoding::Rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn test_wide_gamut() {
+        let red = Encoding::Rgb(255, 0, 0);
+        assert_eq!(red.get_display_p3().unwrap(), DisplayP3::new(918, 200, 139));
+        assert_eq!(red.get_rec2020().unwrap(), Rec2020::new(792, 231, 74));
+        assert!(red.in_srgb_gamut().unwrap());
+
+        let p3_red = DisplayP3::new(918, 200, 139).encode();
+        assert_rgb_close(p3_red.get_rgb().unwrap(), &Rgb::new(255, 0, 0), 1);
+        assert!(p3_red.in_srgb_gamut().unwrap());
+
+        let rec_red = Rec2020::new(792, 231, 74).encode();
+        assert_rgb_close(rec_red.get_rgb().unwrap(), &Rgb::new(255, 0, 0), 1);
+    }
+
+    #[test]
+    fn out_of_gamut_colors_are_mapped_not_clipped() {
+        let p3_green = Encoding::DisplayP3(0, 1000, 0);
+        assert!(!p3_green.in_srgb_gamut().unwrap());
+
+        // mapping keeps lightness and hue to within the clip allowance of 0.02 deltaEOK
+        let source = p3_green.get_oklch().unwrap();
+        let mapped = p3_green.get_rgb().unwrap().encode().get_oklch().unwrap();
+        println!("source: {:?}, mapped: {:?}", source, mapped);
+        assert!((source.h as i32 - mapped.h as i32).abs() <= 4);
+        assert!((source.l as i32 - mapped.l as i32).abs() <= 20);
+        assert!(mapped.c < source.c);
+
+        assert!(!Encoding::Oklch(700, 400, 150).in_srgb_gamut().unwrap());
+        assert!(Encoding::Oklch(700, 100, 150).in_srgb_gamut().unwrap());
+    }
+
+    #[test]
+    fn test_kelvin() {
+        let tests: HashMap<Encoding, Rgb> = HashMap::from([
+            (Encoding::Kelvin(1900), Rgb::new(255, 132, 0)),
+            (Encoding::Kelvin(2700), Rgb::new(255, 173, 89)),
+            // the blackbody at d65's temperature sits just off the daylight locus
+            (Encoding::Kelvin(6504), Rgb::new(255, 249, 254)),
+            (Encoding::Kelvin(10000), Rgb::new(205, 217, 255)),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match input.get_rgb() {
+                Ok(result) => assert_rgb_close(result, &expected, 1),
+                Err(e) => panic!("{e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn kelvin_round_trips_through_rgb() {
+        for k in [2000, 2700, 4000, 5000, 6504, 9000] {
+            let rgb = Encoding::Kelvin(k).get_rgb().unwrap();
+            let result = rgb.encode().get_kelvin().unwrap();
+            println!("input: {k}, rgb: {:?}, result: {:?}", rgb, result);
+            assert!((result.k as i32 - k as i32).abs() <= k as i32 / 100);
+        }
+    }
+
+    #[test]
+    fn test_ansi256() {
+        let tests: HashMap<Encoding, u8> = HashMap::from([
+            (Encoding::Rgb(0, 0, 0), 16),
+            (Encoding::Rgb(255, 255, 255), 231),
+            (Encoding::Rgb(255, 0, 0), 196),
+            (Encoding::Rgb(95, 135, 175), 67),
+            (Encoding::Rgb(128, 128, 128), 244),
+            (Encoding::Rgb(8, 8, 8), 232),
+            (Encoding::Hex(0xCD5C5C), 167),
+            (Encoding::Ansi256(9), 9),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match input.get_ansi256() {
+                Ok(result) => assert_eq!(result, Ansi256::new(expected)),
+                Err(e) => panic!("{e}"),
+            }
+        }
+
+        assert_eq!(
+            Encoding::Ansi256(67).get_rgb().unwrap(),
+            Rgb::new(95, 135, 175)
+        );
+        assert_eq!(
+            Encoding::Ansi256(255).get_rgb().unwrap(),
+            Rgb::new(238, 238, 238)
+        );
+        assert_eq!(
+            Encoding::Ansi256(12).get_rgb().unwrap(),
+            Rgb::new(92, 92, 255)
+        );
+    }
+
+    #[test]
+    fn test_ansi16() {
+        let tests: HashMap<Encoding, u8> = HashMap::from([
+            (Encoding::Rgb(0, 0, 0), 0),
+            (Encoding::Rgb(250, 10, 10), 9),
+            (Encoding::Rgb(180, 0, 0), 1),
+            (Encoding::Rgb(240, 240, 240), 7),
+            (Encoding::Rgb(250, 250, 250), 15),
+            (Encoding::Ansi256(196), 9),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match input.get_ansi16() {
+                Ok(result) => assert_eq!(result, Ansi256::new(expected)),
+                Err(e) => panic!("{e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_hsluv() {
+        let tests: HashMap<Encoding, (Hsluv, Hpluv)> = HashMap::from([
+            (
+                Encoding::Rgb(255, 0, 0),
+                (Hsluv::new(12, 1000, 532), Hpluv::new(12, 4267, 532)),
+            ),
+            (
+                Encoding::Rgb(0, 0, 255),
+                (Hsluv::new(266, 1000, 323), Hpluv::new(266, 5134, 323)),
+            ),
+            (
+                Encoding::Rgb(128, 128, 128),
+                (Hsluv::new(0, 0, 536), Hpluv::new(0, 0, 536)),
+            ),
+            (
+                Encoding::Rgb(255, 255, 255),
+                (Hsluv::new(0, 0, 1000), Hpluv::new(0, 0, 1000)),
+            ),
+            (
+                Encoding::Rgb(0, 0, 0),
+                (Hsluv::new(0, 0, 0), Hpluv::new(0, 0, 0)),
+            ),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            match (input.get_hsluv(), input.get_hpluv()) {
+                (Ok(hsluv), Ok(hpluv)) => {
+                    assert_eq!(hsluv, expected.0);
+                    assert_eq!(hpluv, expected.1);
+                }
+                (Err(e), _) | (_, Err(e)) => panic!("{e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn hsluv_round_trips_through_color() {
+        for rgb in [
+            Rgb::new(70, 130, 180),
+            Rgb::new(240, 128, 128),
+            Rgb::new(34, 139, 34),
+            Rgb::new(255, 255, 0),
+        ] {
+            let hsluv = rgb.encode().get_hsluv().unwrap();
+            let hpluv = rgb.encode().get_hpluv().unwrap();
+            println!("input: {:?}, hsluv: {:?}, hpluv: {:?}", rgb, hsluv, hpluv);
+            assert_rgb_close(Color::new(hsluv.encode()).unwrap().rgb().clone(), &rgb, 2);
+            assert_rgb_close(Color::new(hpluv.encode()).unwrap().rgb().clone(), &rgb, 2);
+        }
+    }
+
+    type Hop = fn(&Encoding) -> Result<Encoding, PaletteError>;
+
+    // every space behind `ColorSpace` that holds any srgb color to within rounding
+    fn hub_spaces() -> [(&'static str, Hop); 14] {
+        [
+            ("rgb", |e| Ok(e.convert::<Rgb>()?.encode())),
+            ("hex", |e| Ok(e.convert::<Hex>()?.encode())),
+            ("hsl", |e| Ok(e.convert::<Hsl>()?.encode())),
+            ("hsb", |e| Ok(e.convert::<Hsb>()?.encode())),
+            ("cmyk", |e| Ok(e.convert::<Cmyk>()?.encode())),
+            ("oklab", |e| Ok(e.convert::<Oklab>()?.encode())),
+            ("oklch", |e| Ok(e.convert::<Oklch>()?.encode())),
+            ("xyz", |e| Ok(e.convert::<Xyz>()?.encode())),
+            ("lab", |e| Ok(e.convert::<Lab>()?.encode())),
+            ("lch", |e| Ok(e.convert::<Lch>()?.encode())),
+            ("display p3", |e| Ok(e.convert::<DisplayP3>()?.encode())),
+            ("rec2020", |e| Ok(e.convert::<Rec2020>()?.encode())),
+            ("hsluv", |e| Ok(e.convert::<Hsluv>()?.encode())),
+            ("hpluv", |e| Ok(e.convert::<Hpluv>()?.encode())),
+        ]
+    }
+
+    #[test]
+    fn every_pair_of_spaces_round_trips() {
+        let spaces = hub_spaces();
+        for rgb in [
+            Rgb::new(205, 92, 92),
+            Rgb::new(0, 128, 128),
+            Rgb::new(255, 215, 0),
+            Rgb::new(75, 0, 130),
+            Rgb::new(128, 128, 128),
+        ] {
+            for (from, to_first) in spaces {
+                for (to, to_second) in spaces {
+                    println!("input: {:?}, {} -> {}", rgb, from, to);
+                    let result = to_first(&rgb.encode())
+                        .and_then(|first| to_second(&first))
+                        .and_then(|second| second.get_rgb());
+                    // each space quantizes differently, so compare below a noticeable difference
+                    match result
+                        .and_then(|result| DistanceMetric::Ciede2000.distance_rgb(&result, &rgb))
+                    {
+                        Ok(difference) => assert!(difference < 1.0, "off by {difference}"),
+                        Err(e) => panic!("{e}"),
+                    }
+                }
+            }
+        }
+    }
+
+    // kelvin and ansi 256 only hold some colors, and names aren't a space, so they
+    // can't join the pairs above. What they hold should survive every other space
+    #[test]
+    fn lossy_spaces_come_back_through_every_space() {
+        for (to, hop) in hub_spaces() {
+            for k in [2000, 2700, 4000, 5000, 6500, 9000] {
+                println!("input: {k}K, through {to}");
+                let kelvin = hop(&Encoding::Kelvin(k))
+                    .and_then(|e| e.get_kelvin())
+                    .unwrap();
+                // rounding in the other spaces moves the chromaticity, within 2%
+                assert!(kelvin.k.abs_diff(k) <= k / 50, "{kelvin:?}");
+            }
+
+            for i in (16..=255).step_by(7) {
+                println!("input: ansi {i}, through {to}");
+                let ansi = hop(&Encoding::Ansi256(i))
+                    .and_then(|e| e.get_ansi256())
+                    .unwrap();
+                assert_eq!(ansi.i, i);
+            }
+
+            for name in ["Indian Red", "Teal", "Gold", "Indigo", "Steel Blue"] {
+                println!("input: {name}, through {to}");
+                let found = hop(&Encoding::Name(name.to_string()))
+                    .and_then(|e| e.get_name())
+                    .unwrap();
+                assert_eq!(found, name);
+            }
+        }
+    }
+
+    #[test]
+    fn std_conversions_match_encoding() {
+        let rgb = Rgb::new(205, 92, 92).with_alpha(128);
+        let hsl = Hsl::from(rgb.clone());
+        assert_eq!(hsl, rgb.encode().get_hsl().unwrap());
+        assert_eq!(Hsb::from(rgb.clone()), rgb.encode().get_hsb().unwrap());
+        assert_eq!(Hex::from(rgb.clone()), Hex::new(0xCD5C5C).with_alpha(128));
+        assert_eq!(Rgb::try_from(hsl.clone()).unwrap(), rgb);
+
+        let color = Color::from(rgb.clone());
+        assert_eq!(Rgb::from(&color), rgb);
+        assert_eq!(Hsl::from(&color), hsl);
+        assert_eq!(Color::try_from(hsl).unwrap(), color);
+
+        assert!(matches!(
+            Rgb::try_from(Hsl::new(400, 0, 0)),
+            Err(PaletteError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            Hsl::try_from(Hex::new(0x1000000)),
+            Err(PaletteError::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn color_keeps_float_precision() {
+        for input in [
+            Hsl::new(200, 500, 500),
+            Hsl::new(17, 333, 271),
+            Hsl::new(359, 999, 1),
+            Hsl::new(121, 40, 960),
+        ] {
+            println!("input: {:?}", input);
+            match Color::new(input.encode()) {
+                Ok(color) => assert_eq!(*color.hsl(), input),
+                Err(e) => panic!("{e}"),
+            }
+        }
+
+        let teal = Color::new(Encoding::Rgb(0, 128, 128)).unwrap();
+        let mut hsl = teal.hsl().clone();
+        for _ in 0..20 {
+            hsl = Color::new(hsl.encode()).unwrap().hsl().clone();
+        }
+        assert_eq!(hsl, *teal.hsl());
+    }
+
+    #[test]
+    fn conversions_reject_out_of_range_channels() {
+        let tests: HashMap<Encoding, (&str, u32, u32)> = HashMap::from([
+            (Encoding::Hsl(361, 500, 500), ("hue", 361, 360)),
+            (Encoding::Hsl(200, 1001, 500), ("saturation", 1001, 1000)),
+            (Encoding::Hsb(200, 500, 2000), ("brightness", 2000, 1000)),
+            (Encoding::Hex(0x1000000), ("hex", 0x1000000, 0xFFFFFF)),
+            (Encoding::Cmyk(0, 0, 1200, 0), ("yellow", 1200, 1000)),
+            (Encoding::Hsluv(400, 5000, 2000), ("hue", 400, 360)),
+            (Encoding::Hpluv(120, 5000, 2000), ("lightness", 2000, 1000)),
+            (Encoding::Oklab(500, -600, 0), ("a", 600, 500)),
+            (Encoding::Oklch(500, 900, 120), ("chroma", 900, 500)),
+            (Encoding::Lab(1100, 0, 0), ("lightness", 1100, 1000)),
+            (Encoding::Lch(500, 500, 720), ("hue", 720, 360)),
+            (Encoding::DisplayP3(0, 1001, 0), ("green", 1001, 1000)),
+            (Encoding::Rec2020(0, 0, 4000), ("blue", 4000, 1000)),
+            (Encoding::Kelvin(0), ("temperature", 0, 25000)),
+            (Encoding::Kelvin(30000), ("temperature", 30000, 25000)),
+        ]);
+
+        for (input, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", input, expected);
+            // already in the space asked for, still checked
+            let same_space = match input {
+                Encoding::Hsl(..) => input.get_hsl().map(|_| ()),
+                Encoding::Hsb(..) => input.get_hsb().map(|_| ()),
+                Encoding::Hex(_) => input.get_hex().map(|_| ()),
+                Encoding::Cmyk(..) => input.get_cmyk().map(|_| ()),
+                Encoding::Hsluv(..) => input.get_hsluv().map(|_| ()),
+                Encoding::Hpluv(..) => input.get_hpluv().map(|_| ()),
+                Encoding::Oklab(..) => input.get_oklab().map(|_| ()),
+                Encoding::Oklch(..) => input.get_oklch().map(|_| ()),
+                Encoding::Lab(..) => input.get_lab().map(|_| ()),
+                Encoding::Lch(..) => input.get_lch().map(|_| ()),
+                Encoding::DisplayP3(..) => input.get_display_p3().map(|_| ()),
+                Encoding::Rec2020(..) => input.get_rec2020().map(|_| ()),
+                _ => input.get_kelvin().map(|_| ()),
+            };
+            for result in [
+                input.get_rgb().map(|_| ()),
+                input.get_oklab().map(|_| ()),
+                Color::new(input.clone()).map(|_| ()),
+                same_space,
+                input.with_alpha(128).get_hsl().map(|_| ()),
+            ] {
+                match result {
+                    Err(PaletteError::OutOfRange {
+                        channel,
+                        value,
+                        max,
+                    }) => assert_eq!((channel.as_str(), value, max), expected),
+                    other => panic!("expected an out of range error, got {:?}", other),
+                }
+            }
+        }
+
+        assert!(Encoding::Hsb(200, 500, 800).get_hsl().is_ok());
+        assert!(Encoding::Hsl(360, 1000, 0).get_hsl().is_ok());
+        assert!(Encoding::Hpluv(260, 5000, 300).get_hpluv().is_ok());
+        assert!(Encoding::Kelvin(1667).get_kelvin().is_ok());
+    }
 }
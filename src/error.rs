@@ -19,4 +19,34 @@ pub enum PaletteError {
 
     #[error("Unable to display: {0}")]
     Display(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Unknown color name: {0}")]
+    UnknownColorName(String),
+
+    #[error("Unknown color function: {0}()")]
+    UnknownColorFunction(String),
+
+    #[error("Unexpected '{token}' at column {column}, expected {expected}")]
+    UnexpectedToken {
+        token: String,
+        column: usize,
+        expected: String,
+    },
+
+    #[error("{channel} of {value} is out of range, the most it can be is {max}")]
+    OutOfRange {
+        channel: String,
+        value: u32,
+        max: u32,
+    },
+
+    #[error("{function}() takes {expected} values, got {found}")]
+    ArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
 }
@@ -1,11 +1,12 @@
 use crate::{
-    app::App,
-    color_math::{generate_color, generate_palette, generate_palette_from_base, monochromatic},
+    app::{App, EXPLORER_STEPS},
+    color_math::{Harmony, generate_color, monochromatic},
     color_spaces::Color as dis_color,
-    file::{list_palette_names, load_palette, save_palette},
+    file::{export_tokens, list_palette_names, load_palette, save_palette},
     fuzzy::fuzzy_filter,
     input::TextInput,
     mode::{RetryAction, UiMode},
+    strategy::generate_distinct,
 };
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
 use std::io;
@@ -31,12 +32,48 @@ impl App {
                 }
                 _ => {}
             },
+            UiMode::Harmony { .. } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_harmony_key_event(key_event)
+                }
+                _ => {}
+            },
+            UiMode::Explorer { .. } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_explorer_key_event(key_event)
+                }
+                _ => {}
+            },
+            UiMode::Scale { .. } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_scale_key_event(key_event)
+                }
+                _ => {}
+            },
+            UiMode::Contrast { .. } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_contrast_key_event(key_event)
+                }
+                _ => {}
+            },
+            UiMode::Export { .. } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_export_event(key_event)
+                }
+                _ => {}
+            },
             UiMode::Save { input: _ } => match event::read()? {
                 Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
                     self.handle_save_event(key_event)
                 }
                 _ => {}
-            }, // need to add save mode, edit mode, copy mode(?)
+            }, // need to add copy mode(?)
+            UiMode::Edit { input: _ } => match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_edit_event(key_event)
+                }
+                _ => {}
+            },
             UiMode::Open {
                 input: _,
                 matches: _,
@@ -67,57 +104,50 @@ impl App {
             // quit
             KeyCode::Char('q') => self.exit(),
             // move left or wrap to last
-            KeyCode::Char('h') => {
-                if self.selected > 0 {
-                    self.selected -= 1;
-                }
+            KeyCode::Char('h') if self.selected > 0 => {
+                self.selected -= 1;
             }
             // move right or wrap to first
-            KeyCode::Char('l') => {
-                if self.selected + 1 < self.colors.len() {
-                    self.selected += 1;
-                }
+            KeyCode::Char('l') if self.selected + 1 < self.colors.len() => {
+                self.selected += 1;
             }
             // randomize all unlocked colors
             KeyCode::Char(' ') => {
-                if self.locked {
-                    let mut slots: Vec<Option<dis_color>> = self
-                        .colors
-                        .iter()
-                        .map(|c| if c.locked { Some(c.clone()) } else { None })
-                        .collect();
+                self.reseed(rand::random());
+                let mut slots: Vec<Option<dis_color>> = self
+                    .colors
+                    .iter()
+                    .map(|c| if c.locked { Some(c.clone()) } else { None })
+                    .collect();
 
-                    let locked_colors: Vec<dis_color> =
-                        slots.iter().filter_map(|c| c.clone()).collect();
+                let locked_colors: Vec<dis_color> =
+                    slots.iter().filter_map(|c| c.clone()).collect();
 
-                    let mut generated = match generate_palette_from_base(
-                        &locked_colors,
-                        self.colors.len() - locked_colors.len(),
-                    ) {
-                        Ok(palette) => palette,
-                        Err(e) => {
-                            self.error = Some(e);
-                            self.retry_action =
-                                Some(RetryAction::GenerateFrom(locked_colors, self.colors.len()));
-                            return;
-                        }
-                    };
-
-                    for slot in slots.iter_mut() {
-                        if slot.is_none() {
-                            *slot = Some(generated.remove(0));
-                        }
-                    }
-                    self.colors = slots.into_iter().map(Option::unwrap).collect();
-                    return;
-                }
-                self.colors = match generate_palette(self.colors.len()) {
-                    Ok(palette) => palette,
+                let mut generated = match generate_distinct(
+                    self.palette_strategy().as_ref(),
+                    &locked_colors,
+                    self.colors.len() - locked_colors.len(),
+                    self.working_space,
+                    &mut self.rng,
+                ) {
+                    Ok(palette) => palette.into_iter(),
                     Err(e) => {
                         self.error = Some(e);
-                        self.retry_action = Some(RetryAction::Generate(self.colors.len() - 1));
+                        let count = self.colors.len() - locked_colors.len();
+                        self.retry_action = Some(RetryAction::GenerateFrom(locked_colors, count));
                         return;
                     }
+                };
+
+                for slot in slots.iter_mut() {
+                    if slot.is_none() {
+                        *slot = generated.next();
+                    }
+                }
+                self.colors = slots.into_iter().flatten().collect();
+                // the seed alone doesn't give back the locked colors
+                if !locked_colors.is_empty() {
+                    self.seed = None;
                 }
             }
             // Lock a color, prevent randomization
@@ -139,7 +169,8 @@ impl App {
                 if self.colors[self.selected].locked {
                     return;
                 }
-                self.colors[self.selected] = match generate_color() {
+                self.colors[self.selected] = match generate_color(self.working_space, &mut self.rng)
+                {
                     Ok(color) => color,
                     Err(e) => {
                         self.error = Some(e);
@@ -147,17 +178,17 @@ impl App {
                         return;
                     }
                 };
+                self.seed = None;
             }
             // select from monochromatic scale of color
             KeyCode::Char('m') => {
                 if matches!(self.mode, UiMode::Normal) {
-                    let options = match monochromatic(&self.colors[self.selected].hsl) {
+                    let color = &self.colors[self.selected];
+                    let options = match monochromatic(color, self.working_space) {
                         Ok(palette) => palette,
                         Err(e) => {
                             self.error = Some(e);
-                            self.retry_action = Some(RetryAction::Monochrome(
-                                self.colors[self.selected].hsl.clone(),
-                            ));
+                            self.retry_action = Some(RetryAction::Monochrome(color.clone()));
                             return;
                         }
                     };
@@ -169,6 +200,78 @@ impl App {
                     };
                 }
             }
+            // explore tints, shades and tones of color
+            KeyCode::Char('t') => {
+                if matches!(self.mode, UiMode::Normal)
+                    && let Err(e) = self.enter_explorer(EXPLORER_STEPS)
+                {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Explorer(EXPLORER_STEPS));
+                }
+            }
+            // select from the 50 to 950 tonal scale of color
+            KeyCode::Char('T') => {
+                if matches!(self.mode, UiMode::Normal)
+                    && let Err(e) = self.enter_scale()
+                {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Scale);
+                }
+            }
+            // show how every color reads on every other
+            KeyCode::Char('C') => {
+                self.mode = UiMode::Contrast { apca: false };
+            }
+            // export the tonal scales of the palette as design tokens
+            KeyCode::Char('x') => {
+                self.mode = UiMode::Export {
+                    input: TextInput::new(),
+                };
+            }
+            // select from the harmony partners of color
+            KeyCode::Char('p') => {
+                if matches!(self.mode, UiMode::Normal)
+                    && let Err(e) = self.enter_harmony_mode()
+                {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Partners);
+                }
+            }
+            // recolor the unlocked columns with the next harmony of the selected color
+            KeyCode::Char('H') => {
+                let rule = self.harmony.map_or(Harmony::ALL[0], Harmony::next);
+                if let Err(e) = self.apply_harmony(rule) {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Harmony(rule));
+                }
+            }
+            // cycle the strategy new palettes are made with
+            KeyCode::Char('g') => {
+                self.cycle_strategy();
+            }
+            // cycle the space new colors and scales are made in
+            KeyCode::Char('w') => {
+                self.working_space = self.working_space.next();
+            }
+            // cycle the dictionary colors are named from
+            KeyCode::Char('d') => {
+                if let Err(e) = self.cycle_dictionary() {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Dictionary);
+                }
+            }
+            // cycle the metric colors are named with
+            KeyCode::Char('M') => {
+                self.cycle_metric();
+            }
+            // cycle which names are shown under the colors
+            KeyCode::Char('n') => {
+                self.name_style = self.name_style.next();
+            }
+            // cycle what translucent colors are shown over
+            KeyCode::Char('b') => {
+                self.backdrop = self.backdrop.next();
+            }
             // save the palette
             KeyCode::Char('s') => {
                 self.mode = UiMode::Save {
@@ -194,23 +297,21 @@ impl App {
             // a | 1 for all encodings,
             // h | 2 for hsl,
             // r | 3 for rgb,
-            KeyCode::Char('c') => {
-                return;
-            }
+            KeyCode::Char('c') => {}
             // edit selected color
             KeyCode::Char('e') => {
-                return;
+                self.mode = UiMode::Edit {
+                    input: TextInput::new(),
+                };
             }
             // add another color
             // does it need a click?
-            KeyCode::Char('a') => {
-                return;
-            }
+            KeyCode::Char('a') => {}
             _ => {}
         }
     }
 
-    fn handle_monochrome_key_event(&mut self, key_event: KeyEvent) {
+    pub(crate) fn handle_monochrome_key_event(&mut self, key_event: KeyEvent) {
         if self.error.is_some() {
             match key_event.code {
                 KeyCode::Enter | KeyCode::Esc => {
@@ -229,18 +330,127 @@ impl App {
         } = &mut self.mode
         {
             match key_event.code {
-                KeyCode::Char('j') => {
-                    if *selected + 1 < options.len() {
-                        *selected += 1;
+                KeyCode::Char('j') if *selected + 1 < options.len() => {
+                    *selected += 1;
+                }
+                KeyCode::Char('k') if *selected > 0 => {
+                    *selected -= 1;
+                }
+                KeyCode::Enter => {
+                    self.colors[*column] = options[*selected].clone();
+                    self.seed = None;
+                    self.mode = UiMode::Normal;
+                }
+                KeyCode::Esc => {
+                    self.mode = UiMode::Normal;
+                }
+                KeyCode::Char('q') => {
+                    self.exit();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn handle_harmony_key_event(&mut self, key_event: KeyEvent) {
+        if self.error.is_some() {
+            match key_event.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.error = None;
+                }
+                KeyCode::Char('r') => {
+                    self.retry();
+                }
+                _ => {}
+            }
+        }
+        if let UiMode::Harmony {
+            column,
+            options,
+            selected,
+        } = &mut self.mode
+        {
+            match key_event.code {
+                KeyCode::Char('j') if *selected + 1 < options.len() => {
+                    *selected += 1;
+                }
+                KeyCode::Char('k') if *selected > 0 => {
+                    *selected -= 1;
+                }
+                KeyCode::Enter => {
+                    self.colors[*column] = options[*selected].1.clone();
+                    self.seed = None;
+                    self.mode = UiMode::Normal;
+                }
+                // the whole harmony of the highlighted partner, across the unlocked columns
+                KeyCode::Char('a') => {
+                    let rule = options[*selected].0;
+                    self.selected = *column;
+                    self.mode = UiMode::Normal;
+                    if let Err(e) = self.apply_harmony(rule) {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Harmony(rule));
                     }
                 }
-                KeyCode::Char('k') => {
-                    if *selected > 0 {
-                        *selected -= 1;
+                KeyCode::Esc => {
+                    self.mode = UiMode::Normal;
+                }
+                KeyCode::Char('q') => {
+                    self.exit();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn handle_explorer_key_event(&mut self, key_event: KeyEvent) {
+        if self.error.is_some() {
+            match key_event.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.error = None;
+                }
+                KeyCode::Char('r') => {
+                    self.retry();
+                }
+                _ => {}
+            }
+        }
+        if let UiMode::Explorer {
+            column,
+            grid,
+            row,
+            col,
+            steps,
+        } = &mut self.mode
+        {
+            match key_event.code {
+                KeyCode::Char('h') if *col > 0 => {
+                    *col -= 1;
+                }
+                KeyCode::Char('l') if *col + 1 < grid[*row].len() => {
+                    *col += 1;
+                }
+                KeyCode::Char('j') if *row + 1 < grid.len() => {
+                    *row += 1;
+                }
+                KeyCode::Char('k') if *row > 0 => {
+                    *row -= 1;
+                }
+                // more or fewer steps, starting again from the column's color
+                KeyCode::Char('+') | KeyCode::Char('-') => {
+                    let steps = match key_event.code {
+                        KeyCode::Char('+') => *steps + 1,
+                        _ => steps.saturating_sub(1),
+                    };
+                    self.selected = *column;
+                    if let Err(e) = self.enter_explorer(steps) {
+                        self.error = Some(e);
+                        self.retry_action = Some(RetryAction::Explorer(steps));
                     }
                 }
                 KeyCode::Enter => {
-                    self.colors[*column] = options[*selected].clone();
+                    self.colors[*column] = grid[*row][*col].clone();
+                    self.seed = None;
                     self.mode = UiMode::Normal;
                 }
                 KeyCode::Esc => {
@@ -254,6 +464,84 @@ impl App {
         }
     }
 
+    pub(crate) fn handle_scale_key_event(&mut self, key_event: KeyEvent) {
+        if self.error.is_some() {
+            match key_event.code {
+                KeyCode::Enter | KeyCode::Esc => {
+                    self.error = None;
+                }
+                KeyCode::Char('r') => {
+                    self.retry();
+                }
+                _ => {}
+            }
+        }
+        if let UiMode::Scale {
+            column,
+            scale,
+            selected,
+        } = &mut self.mode
+        {
+            match key_event.code {
+                KeyCode::Char('j') if *selected + 1 < scale.len() => {
+                    *selected += 1;
+                }
+                KeyCode::Char('k') if *selected > 0 => {
+                    *selected -= 1;
+                }
+                KeyCode::Enter => {
+                    self.colors[*column] = scale[*selected].1.clone();
+                    self.seed = None;
+                    self.mode = UiMode::Normal;
+                }
+                KeyCode::Esc => {
+                    self.mode = UiMode::Normal;
+                }
+                KeyCode::Char('q') => {
+                    self.exit();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn handle_contrast_key_event(&mut self, key_event: KeyEvent) {
+        let UiMode::Contrast { apca } = &mut self.mode else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char('a') | KeyCode::Char('A') => *apca = !*apca,
+            KeyCode::Esc | KeyCode::Char('C') => self.mode = UiMode::Normal,
+            KeyCode::Char('q') => self.exit(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_export_event(&mut self, key_event: KeyEvent) {
+        let UiMode::Export { input } = &mut self.mode else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char(c) => input.insert_char(c),
+            KeyCode::Backspace => input.delete_char_before_cursor(),
+            KeyCode::Delete => input.delete_char_after_cursor(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Enter => {
+                let name = input.value().to_string();
+                if let Err(e) = export_tokens(&name, &self.colors) {
+                    self.error = Some(e);
+                    self.retry_action = Some(RetryAction::Export(name));
+                }
+                self.mode = UiMode::Normal;
+            }
+            KeyCode::Esc => self.mode = UiMode::Normal,
+            _ => {}
+        }
+    }
+
     pub(crate) fn handle_save_event(&mut self, key_event: KeyEvent) {
         let UiMode::Save { input } = &mut self.mode else {
             return;
@@ -278,6 +566,35 @@ impl App {
         }
     }
 
+    pub(crate) fn handle_edit_event(&mut self, key_event: KeyEvent) {
+        let UiMode::Edit { input } = &mut self.mode else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char(c) => input.insert_char(c),
+            KeyCode::Backspace => input.delete_char_before_cursor(),
+            KeyCode::Delete => input.delete_char_after_cursor(),
+            KeyCode::Left => input.move_left(),
+            KeyCode::Right => input.move_right(),
+            KeyCode::Enter => {
+                match input.value().parse::<dis_color>() {
+                    Ok(mut color) => {
+                        if let Some(selected) = self.colors.get_mut(self.selected) {
+                            color.locked = selected.locked;
+                            *selected = color;
+                            self.seed = None;
+                        }
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+                self.mode = UiMode::Normal;
+            }
+            KeyCode::Esc => self.mode = UiMode::Normal,
+            _ => {}
+        }
+    }
+
     fn handle_open_event(&mut self, key_event: KeyEvent) {
         let UiMode::Open {
             input,
@@ -304,15 +621,11 @@ impl App {
                 *matches = fuzzy_filter(&self.all_palette_names, input.value());
                 *selected = 0;
             }
-            KeyCode::Down => {
-                if *selected + 1 < matches.len() {
-                    *selected += 1;
-                }
+            KeyCode::Down if *selected + 1 < matches.len() => {
+                *selected += 1;
             }
-            KeyCode::Up => {
-                if *selected - 1 > 0 {
-                    *selected -= 1;
-                };
+            KeyCode::Up if *selected > 0 => {
+                *selected -= 1;
             }
             KeyCode::Left => input.move_left(),
             KeyCode::Right => input.move_right(),
@@ -330,6 +643,7 @@ impl App {
                         return;
                     }
                 };
+                self.seed = None;
                 self.mode = UiMode::Normal;
             }
             KeyCode::Esc => self.mode = UiMode::Normal,
@@ -338,18 +652,18 @@ impl App {
     }
 
     pub fn handle_shutdown_error(&mut self) -> io::Result<()> {
-        if let Event::Key(key_event) = event::read()? {
-            if key_event.kind == KeyEventKind::Press {
-                match key_event.code {
-                    KeyCode::Char('r') | KeyCode::Enter => {
-                        self.retry();
-                    }
-                    KeyCode::Esc | KeyCode::Char('q') => {
-                        self.error = None;
-                        self.retry_action = None;
-                    }
-                    _ => {}
+        if let Event::Key(key_event) = event::read()?
+            && key_event.kind == KeyEventKind::Press
+        {
+            match key_event.code {
+                KeyCode::Char('r') | KeyCode::Enter => {
+                    self.retry();
                 }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.error = None;
+                    self.retry_action = None;
+                }
+                _ => {}
             }
         }
         Ok(())
@@ -1,8 +1,12 @@
-use crate::color_spaces::{Color, Hex, Hsb, Hsl, Rgb};
+use crate::color_math::tonal_scale;
+use crate::color_spaces::{Cmyk, Color, Hex, Hsb, Hsl, OPAQUE, Rgb};
 use crate::error::PaletteError;
+use crate::named_colors::{CSS4, NameDictionary};
 use std::fs::File;
 use std::io::prelude::*;
+use std::ops::RangeInclusive;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 pub fn load_palette(palette_name: &str) -> Result<Vec<Color>, PaletteError> {
     let mut palette = Vec::new();
@@ -13,49 +17,70 @@ pub fn load_palette(palette_name: &str) -> Result<Vec<Color>, PaletteError> {
     file.read_to_string(&mut contents)?;
 
     for (line_num, line) in contents.lines().enumerate() {
-        let color: Vec<&str> = line.split(" ").collect();
+        let mut color: Vec<&str> = line.split(" ").collect();
 
-        if color.len() != 6 {
+        // palettes saved before cmyk was stored have 6 values, cmyk is derived for those
+        let legacy = color.len() == 6;
+        if legacy {
+            color.insert(4, "");
+        } else if color.len() != 7 {
             return Err(PaletteError::InvalidFormat(format!(
-                "Line {}: expected 6 values, got {}",
+                "Line {}: expected 7 values, got {}",
                 line_num + 1,
                 color.len()
             )));
         }
 
-        let rgb: Vec<&str> = color[0].split(",").collect();
+        let line = line_num + 1;
+        let rgb = values(color[0], 3..=4, line)?;
 
         let r: u8 = rgb[0].trim().parse()?;
         let g: u8 = rgb[1].trim().parse()?;
         let b: u8 = rgb[2].trim().parse()?;
-        let rgb = Rgb::new(r, g, b);
+        let a: u8 = match rgb.get(3) {
+            Some(a) => a.trim().parse()?,
+            None => OPAQUE,
+        };
+        let rgb = Rgb::new(r, g, b).with_alpha(a);
 
-        let hsl: Vec<&str> = color[1].split(",").collect();
+        let hsl = values(color[1], 3..=3, line)?;
 
         let h: u16 = hsl[0].trim().parse()?;
         let s: u16 = hsl[1].trim().parse()?;
         let l: u16 = hsl[2].trim().parse()?;
-        let hsl = Hsl::new(h, s, l);
+        let hsl = Hsl::try_new(h, s, l)?.with_alpha(a);
 
-        let hsb: Vec<&str> = color[2].split(",").collect();
+        let hsb = values(color[2], 3..=3, line)?;
 
         let h: u16 = hsb[0].trim().parse()?;
         let s: u16 = hsb[1].trim().parse()?;
         let b: u16 = hsb[2].trim().parse()?;
-        let hsb = Hsb::new(h, s, b);
+        let hsb = Hsb::try_new(h, s, b)?.with_alpha(a);
 
         let hex: u32 = color[3].trim().parse()?;
-        let hex = Hex::new(hex);
+        let hex = Hex::try_new(hex)?.with_alpha(a);
 
-        let name: String = color[4].trim().to_string().replace(",", " ");
+        let cmyk = if legacy {
+            rgb.encode().get_cmyk()?
+        } else {
+            let cmyk = values(color[4], 4..=4, line)?;
 
-        let locked: bool = color[5].trim().parse()?;
+            let c: u16 = cmyk[0].trim().parse()?;
+            let m: u16 = cmyk[1].trim().parse()?;
+            let y: u16 = cmyk[2].trim().parse()?;
+            let k: u16 = cmyk[3].trim().parse()?;
+            Cmyk::try_new(c, m, y, k)?
+        };
 
-        let color = Color::new_raw(rgb, hsl, hsb, hex, name, locked);
+        let name: String = color[5].trim().to_string().replace(",", " ");
+
+        let locked: bool = color[6].trim().parse()?;
+
+        let color = Color::from_stored(rgb, hsl, hsb, hex, cmyk, name, locked)?;
         palette.push(color);
     }
 
-    if palette.len() < 1 {
+    if palette.is_empty() {
         return Err(PaletteError::Display(
             "not enough colors in cache".to_string(),
         ));
@@ -64,15 +89,89 @@ pub fn load_palette(palette_name: &str) -> Result<Vec<Color>, PaletteError> {
     Ok(palette)
 }
 
+// the comma separated values of one field, checked so a short field can't panic
+fn values(
+    field: &str,
+    count: RangeInclusive<usize>,
+    line: usize,
+) -> Result<Vec<&str>, PaletteError> {
+    let values: Vec<&str> = field.split(",").collect();
+    if !count.contains(&values.len()) {
+        return Err(PaletteError::InvalidFormat(format!(
+            "Line {line}: expected {} comma separated values in '{field}', got {}",
+            count.start(),
+            values.len()
+        )));
+    }
+    Ok(values)
+}
+
 pub fn save_palette(palette_name: &str, palette: Vec<Color>) -> Result<(), PaletteError> {
     let mut file = File::create(palette_name)?;
     for color in palette {
-        file.write(color.color_string().as_bytes())?;
+        file.write_all(color.color_string().as_bytes())?;
     }
 
     Ok(())
 }
 
+/// Writes the tonal scale of every color as design tokens to `<name>.json` in the
+/// tokens dir.
+pub fn export_tokens(name: &str, palette: &[Color]) -> Result<(), PaletteError> {
+    let path = tokens_dir()?.join(format!("{name}.json"));
+    std::fs::write(path, tokens_json(palette)?)?;
+    Ok(())
+}
+
+/// The tonal scales of `palette` in the design tokens community group format, one
+/// group per color named after it, one token per step.
+pub fn tokens_json(palette: &[Color]) -> Result<String, PaletteError> {
+    let mut groups: Vec<String> = Vec::with_capacity(palette.len());
+    let mut used: Vec<String> = Vec::with_capacity(palette.len());
+    for color in palette {
+        let base = token_name(color.name());
+        let mut name = base.clone();
+        // two colors with one name get told apart by number
+        for n in 2.. {
+            if !used.contains(&name) {
+                break;
+            }
+            name = format!("{base}-{n}");
+        }
+
+        let steps: Vec<String> = tonal_scale(color)?
+            .iter()
+            .map(|(step, color)| {
+                format!(
+                    "      \"{step}\": {{ \"$type\": \"color\", \"$value\": \"{}\" }}",
+                    color.hex()
+                )
+            })
+            .collect();
+        groups.push(format!("    \"{name}\": {{\n{}\n    }}", steps.join(",\n")));
+        used.push(name);
+    }
+
+    Ok(format!(
+        "{{\n  \"color\": {{\n{}\n  }}\n}}\n",
+        groups.join(",\n")
+    ))
+}
+
+// lower case words joined by dashes, "Indian Red" is indian-red
+fn token_name(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect();
+    if words.is_empty() {
+        "color".to_string()
+    } else {
+        words.join("-")
+    }
+}
+
 pub fn palette_dir() -> Result<PathBuf, PaletteError> {
     let project_dirs = directories::ProjectDirs::from("", "", "palette-gen")
         .ok_or_else(|| PaletteError::Display("could not resolve data dir".into()))?;
@@ -87,24 +186,62 @@ pub fn list_palette_names() -> Result<Vec<String>, PaletteError> {
 
     for entry in std::fs::read_dir(dir)? {
         let entry = entry?;
-        if entry.file_type()?.is_file() {
-            if let Some(name) = entry.file_name().to_str() {
-                if name != "cache" {
-                    names.push(name.to_string());
-                }
-            }
+        if entry.file_type()?.is_file()
+            && let Some(name) = entry.file_name().to_str()
+            && name != "cache"
+        {
+            names.push(name.to_string());
         }
     }
     names.sort();
     Ok(names)
 }
 
+// name dictionaries are csv files kept apart from the palettes
+pub fn dictionary_dir() -> Result<PathBuf, PaletteError> {
+    let dir = palette_dir()?.join("dictionaries");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+// exported design tokens are kept apart from the palettes
+pub fn tokens_dir() -> Result<PathBuf, PaletteError> {
+    let dir = palette_dir()?.join("tokens");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// The built in dictionary first, then every csv in the dictionary dir by file stem.
+pub fn list_dictionary_names() -> Result<Vec<String>, PaletteError> {
+    let mut names = Vec::new();
+
+    for entry in std::fs::read_dir(dictionary_dir()?)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == "csv")
+            && let Some(name) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    names.insert(0, CSS4.to_string());
+    Ok(names)
+}
+
+pub fn load_dictionary(name: &str) -> Result<Arc<NameDictionary>, PaletteError> {
+    if name == CSS4 {
+        return Ok(NameDictionary::css4());
+    }
+    let contents = std::fs::read_to_string(dictionary_dir()?.join(format!("{name}.csv")))?;
+    Ok(Arc::new(NameDictionary::from_csv(name, &contents)?))
+}
+
 // --------------------------
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::color_math::generate_palette;
+    use crate::color_math::{PaletteRng, WorkingSpace, generate_palette};
     //use crate::color_spaces::{Color, Hex};
 
     // #[test]
@@ -127,16 +264,142 @@ mod tests {
     //     };
     // }
 
+    #[test]
+    fn tokens_hold_every_step_of_every_color() {
+        let indian_red = Color::new(Rgb::new(205, 92, 92).encode()).unwrap();
+        let json = tokens_json(&[indian_red.clone(), indian_red]).unwrap();
+
+        assert!(json.starts_with("{\n  \"color\": {\n    \"indian-red\": {\n"));
+        assert!(json.contains("\n    \"indian-red-2\": {\n"));
+        assert_eq!(json.matches("\"$type\": \"color\"").count(), 22);
+        assert!(json.contains("\"50\": { \"$type\": \"color\", \"$value\": \"#"));
+        // lightness 582 is nearest step 500
+        assert!(json.contains("\"500\": { \"$type\": \"color\", \"$value\": \"#cd5c5c\" }"));
+        assert_eq!(token_name("Dark Slate Gray"), "dark-slate-gray");
+        assert_eq!(token_name(" - "), "color");
+    }
+
     #[test]
     fn save_load_round_trips() {
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("cache");
         let path_str = path.to_str().unwrap();
 
-        let original = generate_palette(3).unwrap();
+        let original =
+            generate_palette(3, WorkingSpace::default(), &mut PaletteRng::default()).unwrap();
         save_palette(path_str, original.clone()).unwrap();
         let loaded = load_palette(path_str).unwrap();
 
         assert_eq!(original, loaded);
     }
+
+    #[test]
+    fn save_load_round_trips_in_every_space() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("spaces");
+        let path_str = path.to_str().unwrap();
+
+        for space in [
+            WorkingSpace::Hsl,
+            WorkingSpace::Hsluv,
+            WorkingSpace::Hpluv,
+            WorkingSpace::Oklch,
+        ] {
+            println!("input: {space}");
+            let original = generate_palette(50, space, &mut PaletteRng::seeded(11)).unwrap();
+            save_palette(path_str, original.clone()).unwrap();
+            assert_eq!(load_palette(path_str).unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn loads_palettes_saved_without_cmyk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("legacy");
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(
+            path_str,
+            "205,92,92 0,531,582 0,551,804 13458524 Indian,Red false\n",
+        )
+        .unwrap();
+        let loaded = load_palette(path_str).unwrap();
+
+        assert_eq!(*loaded[0].cmyk(), Cmyk::new(0, 551, 551, 196));
+        assert_eq!(loaded[0].name(), "Indian Red");
+    }
+
+    #[test]
+    fn save_load_keeps_alpha() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scrim");
+        let path_str = path.to_str().unwrap();
+
+        let original = vec![
+            Color::new(Rgb::new(0, 0, 0).with_alpha(128).encode()).unwrap(),
+            Color::new(Hex::new(0xf2d7ee).encode()).unwrap(),
+        ];
+        save_palette(path_str, original.clone()).unwrap();
+        let loaded = load_palette(path_str).unwrap();
+
+        assert_eq!(loaded[0].alpha(), 128);
+        assert_eq!(*loaded[0].hex(), Hex::new(0).with_alpha(128));
+        assert!(loaded[1].is_opaque());
+        assert_eq!(original, loaded);
+    }
+
+    #[test]
+    fn malformed_palettes_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken");
+        let path_str = path.to_str().unwrap();
+
+        std::fs::write(
+            path_str,
+            "205,92,92 0,1531,582 0,551,804 13458524 0,551,551,196 Indian,Red false\n",
+        )
+        .unwrap();
+        match load_palette(path_str) {
+            Err(PaletteError::OutOfRange {
+                channel,
+                value,
+                max,
+            }) => {
+                assert_eq!((channel.as_str(), value, max), ("saturation", 1531, 1000))
+            }
+            other => panic!("expected an out of range error, got {:?}", other),
+        }
+
+        // a hue nowhere near the rgb
+        std::fs::write(
+            path_str,
+            "205,92,92 120,531,582 0,551,804 13458524 0,551,551,196 Indian,Red false\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_palette(path_str),
+            Err(PaletteError::InvalidFormat(_))
+        ));
+
+        // a hex one off
+        std::fs::write(
+            path_str,
+            "205,92,92 0,531,582 0,551,804 13458525 0,551,551,196 Indian,Red false\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_palette(path_str),
+            Err(PaletteError::InvalidFormat(_))
+        ));
+
+        std::fs::write(
+            path_str,
+            "205,92 0,531,582 0,551,804 13458524 0,551,551,196 Indian,Red false\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_palette(path_str),
+            Err(PaletteError::InvalidFormat(_))
+        ));
+    }
 }
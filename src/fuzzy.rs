@@ -10,6 +10,6 @@ pub fn fuzzy_filter(names: &[String], query: &str) -> Vec<String> {
         .iter()
         .filter_map(|n| matcher.fuzzy_match(n, query).map(|score| (score, n)))
         .collect();
-    scored.sort_by(|a, b| b.0.cmp(&a.0));
+    scored.sort_by_key(|s| std::cmp::Reverse(s.0));
     scored.into_iter().map(|(_, n)| n.clone()).collect()
 }
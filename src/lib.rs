@@ -1,6 +1,10 @@
 pub mod app;
+pub mod args;
+pub mod color_distance;
 pub mod color_math;
 pub mod color_spaces;
+pub mod css;
+pub mod describe;
 pub mod encoding;
 pub mod error;
 pub mod event;
@@ -9,4 +13,7 @@ pub mod fuzzy;
 pub mod input;
 pub mod mode;
 pub mod named_colors;
+pub mod nearest;
+pub mod strategy;
+pub mod terminal;
 pub mod ui;
@@ -1,10 +1,27 @@
 use std::io;
 
-use palette_maker::app::App;
+use palette_maker::{
+    app::App,
+    args::{Args, USAGE},
+    terminal::ColorSupport,
+};
 
 fn main() -> io::Result<()> {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{e}\n{USAGE}");
+            std::process::exit(2);
+        }
+    };
+
     let mut terminal = ratatui::init();
-    let app_result = App::default().run(&mut terminal);
+    let mut app = App {
+        color_support: ColorSupport::detect(),
+        args,
+        ..Default::default()
+    };
+    let app_result = app.run(&mut terminal);
     ratatui::restore();
     app_result
 }
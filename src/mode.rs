@@ -1,7 +1,4 @@
-use crate::{
-    color_spaces::{Color as dis_color, Hsl},
-    input::TextInput,
-};
+use crate::{color_math::Harmony, color_spaces::Color as dis_color, input::TextInput};
 
 #[derive(Debug, Clone, Default)]
 pub enum UiMode {
@@ -12,9 +9,38 @@ pub enum UiMode {
         options: Vec<dis_color>,
         selected: usize,
     },
+    Harmony {
+        column: usize,
+        // every partner of each suggested rule, a row apiece
+        options: Vec<(Harmony, dis_color)>,
+        selected: usize,
+    },
+    Explorer {
+        column: usize,
+        // rows from tints to shades, columns toned toward gray
+        grid: Vec<Vec<dis_color>>,
+        row: usize,
+        col: usize,
+        steps: usize,
+    },
+    Scale {
+        column: usize,
+        scale: Vec<(u16, dis_color)>,
+        selected: usize,
+    },
+    Contrast {
+        // apca lightness contrast instead of wcag 2.1 ratios
+        apca: bool,
+    },
     Save {
         input: TextInput,
     },
+    Export {
+        input: TextInput,
+    },
+    Edit {
+        input: TextInput,
+    },
     Open {
         input: TextInput,
         matches: Vec<String>,
@@ -26,10 +52,15 @@ pub enum UiMode {
 pub enum RetryAction {
     Startup,
     Save(Vec<dis_color>),
-    Generate(usize),
     GenerateFrom(Vec<dis_color>, usize),
     GenerateSingle,
-    Monochrome(Hsl),
+    Monochrome(dis_color),
     Load(String),
+    Harmony(Harmony),
+    Partners,
+    Explorer(usize),
+    Scale,
+    Export(String),
+    Dictionary,
     List,
 }
@@ -1,148 +1,294 @@
-use std::collections::HashMap;
-use std::sync::LazyLock;
-pub static NAMED_COLORS: LazyLock<HashMap<&str, (u8, u8, u8)>> = LazyLock::new(|| {
-    HashMap::from([
-        ("Indian Red", (205, 92, 92)),
-        ("Light Coral", (240, 128, 128)),
-        ("Salmon", (250, 128, 114)),
-        ("Dark Salmon", (233, 150, 122)),
-        ("Light Salmon", (255, 160, 122)),
-        ("Crimson", (220, 20, 60)),
-        ("Red", (255, 0, 0)),
-        ("Fire Brick", (178, 34, 34)),
-        ("Dark Red", (139, 0, 0)),
-        ("Pink", (155, 192, 203)),
-        ("Light Pink", (255, 182, 193)),
-        ("Hot Pink", (255, 105, 180)),
-        ("Deep Pink", (255, 20, 147)),
-        ("Medium Violet Red", (199, 21, 133)),
-        ("Pale Violet Red", (219, 112, 147)),
-        ("Light Salmon", (255, 160, 122)),
-        ("Coral", (255, 127, 80)),
-        ("Tomato", (255, 99, 71)),
-        ("Orange Red", (255, 69, 0)),
-        ("Dark Orange", (255, 140, 0)),
-        ("Orange", (255, 165, 0)),
-        ("Gold", (255, 215, 0)),
-        ("Yellow", (255, 255, 0)),
-        ("Light Yellow", (255, 255, 224)),
-        ("Lemon Chiffon", (255, 250, 205)),
-        ("Light Goldenrod Yellow", (250, 250, 210)),
-        ("Papaya Whip", (255, 239, 213)),
-        ("Mocasin", (255, 228, 181)),
-        ("Peach Puff", (255, 218, 185)),
-        ("Pale Goldenrod", (238, 232, 170)),
-        ("Khaki", (240, 230, 140)),
-        ("Dark Khaki", (189, 183, 107)),
-        ("Lavender", (230, 230, 250)),
-        ("Thistle", (216, 191, 216)),
-        ("Plum", (221, 160, 221)),
-        ("Violet", (238, 130, 238)),
-        ("Orchid", (218, 112, 214)),
-        ("Fuchsia", (255, 0, 255)),
-        ("Magenta", (255, 0, 255)),
-        ("Medium Orchid", (186, 85, 211)),
-        ("Medium Purple", (147, 112, 219)),
-        ("Rebecca Purple", (102, 51, 153)),
-        ("Blue Violet", (138, 43, 226)),
-        ("Dark Violet", (148, 0, 211)),
-        ("Darck Orchid", (153, 50, 204)),
-        ("Dark Magenta", (139, 0, 139)),
-        ("Purple", (128, 0, 128)),
-        ("Indigo", (75, 0, 130)),
-        ("Slate Blue", (106, 90, 205)),
-        ("Dark Slate Blue", (72, 61, 139)),
-        ("Medium Slate Blue", (123, 104, 238)),
-        ("Green Yellow", (173, 255, 47)),
-        ("Chartreuse", (127, 255, 0)),
-        ("Lawn Green", (124, 252, 0)),
-        ("Lime", (0, 255, 0)),
-        ("Lime Green", (50, 205, 50)),
-        ("Pale Green", (152, 251, 152)),
-        ("Light Green", (144, 238, 144)),
-        ("Medium Spring Green", (0, 250, 154)),
-        ("Spring Green", (0, 255, 127)),
-        ("Medium Sea Green", (0, 255, 127)),
-        ("Sea Green", (46, 139, 87)),
-        ("Forest Green", (34, 139, 34)),
-        ("Green", (0, 128, 0)),
-        ("Dark Green", (0, 100, 0)),
-        ("Yellow Green", (154, 205, 50)),
-        ("Olive Drab", (107, 142, 35)),
-        ("Dark Olive Green", (85, 107, 47)),
-        ("Medium Aquamarine", (102, 205, 170)),
-        ("Dark Sea Green", (153, 188, 139)),
-        ("Light Sea Green", (32, 178, 170)),
-        ("Dark Cyan", (0, 139, 139)),
-        ("Teal", (0, 128, 128)),
-        ("Aqua", (0, 255, 255)),
-        ("Cyan", (0, 255, 255)),
-        ("Light Cyan", (224, 255, 255)),
-        ("Pale Turquoise", (175, 238, 238)),
-        ("Aquamarine", (127, 255, 212)),
-        ("Turquoise", (64, 224, 208)),
-        ("Medium Turquoise", (72, 209, 204)),
-        ("Dark Turquoise", (0, 206, 209)),
-        ("Cadet Blue", (95, 158, 160)),
-        ("Steel Blue", (70, 130, 180)),
-        ("Light Steel Blue", (176, 196, 222)),
-        ("Powder Blue", (176, 224, 230)),
-        ("Light Blue", (173, 216, 230)),
-        ("Sky Blue", (135, 206, 235)),
-        ("Light Sky Blue", (135, 206, 250)),
-        ("Deep Sky Blue", (0, 191, 255)),
-        ("Dodger Blue", (30, 144, 255)),
-        ("Cornflower Blue", (100, 159, 237)),
-        ("Medium Slate Blue", (123, 104, 238)),
-        ("Royal Blue", (65, 105, 225)),
-        ("Blue", (0, 0, 255)),
-        ("Medium Blue", (0, 0, 205)),
-        ("Dark Blue", (0, 0, 139)),
-        ("Navy", (0, 0, 128)),
-        ("Midnight Blue", (25, 25, 112)),
-        ("Cornsilk", (255, 248, 220)),
-        ("Blanched Almond", (255, 235, 205)),
-        ("Bisque", (255, 228, 196)),
-        ("Navajo White", (255, 222, 173)),
-        ("Wheat", (245, 222, 179)),
-        ("Burly Wood", (222, 184, 135)),
-        ("Tan", (210, 180, 140)),
-        ("Rosy Brown", (188, 143, 143)),
-        ("Sandy Brown", (244, 164, 96)),
-        ("Goldenrod", (218, 165, 32)),
-        ("Dark Goldenrod", (184, 134, 11)),
-        ("Peru", (205, 233, 63)),
-        ("Chocolate", (210, 105, 30)),
-        ("SaddleBrown", (139, 69, 19)),
-        ("Sienna", (160, 82, 45)),
-        ("Brown", (165, 42, 42)),
-        ("Maroon", (128, 0, 0)),
-        ("White", (255, 255, 255)),
-        ("Snow", (255, 250, 250)),
-        ("Honey Dew", (240, 255, 240)),
-        ("Mint Cream", (245, 255, 250)),
-        ("Azure", (240, 255, 255)),
-        ("Alice Blue", (240, 248, 255)),
-        ("Ghost White", (248, 248, 255)),
-        ("White Smoke", (245, 245, 245)),
-        ("Sea Shell", (255, 245, 238)),
-        ("Beige", (245, 245, 220)),
-        ("Old Lace", (253, 245, 230)),
-        ("Floral White", (255, 250, 240)),
-        ("Ivory", (255, 255, 240)),
-        ("Antique White", (250, 235, 215)),
-        ("Linen", (250, 240, 230)),
-        ("Lavender Blush", (255, 240, 245)),
-        ("Misty Rose", (255, 238, 225)),
-        ("Gainsboro", (220, 220, 220)),
-        ("Light Gray", (211, 211, 211)),
-        ("Silver", (192, 192, 192)),
-        ("Dark Gray", (169, 169, 169)),
-        ("Gray", (128, 128, 128)),
-        ("Dim Gray", (105, 105, 105)),
-        ("Light Slate Gray", (119, 136, 153)),
-        ("Slate Gray", (112, 128, 144)),
-        ("Dark Slate Gray", (47, 79, 79)),
-        ("Black", (0, 0, 0)),
-    ])
+use crate::{
+    color_distance::DistanceMetric, color_spaces::Rgb, error::PaletteError, nearest::NearestColor,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+/// The name the built in dictionary goes by.
+pub const CSS4: &str = "CSS4";
+
+static CSS4_DICTIONARY: LazyLock<Arc<NameDictionary>> = LazyLock::new(|| {
+    Arc::new(
+        NameDictionary::from_csv(CSS4, include_str!("named_colors.csv"))
+            .expect("the built in dictionary is valid csv"),
+    )
 });
+
+static ACTIVE: LazyLock<RwLock<Arc<NameDictionary>>> =
+    LazyLock::new(|| RwLock::new(NameDictionary::css4()));
+
+/// A list of named colors read from a csv with `name` and `hex` columns. Any other
+/// columns are ignored, so exports from other tools load as they are.
+#[derive(Debug, PartialEq)]
+pub struct NameDictionary {
+    pub name: String,
+    // file order, the first of two names for the same color is the one it's called
+    entries: Vec<(String, Rgb)>,
+    // position in entries by name_key
+    by_name: HashMap<String, usize>,
+    index: NearestColor,
+}
+
+// the index is in oklab, other metrics pick from this many of its closest
+const CANDIDATES: usize = 8;
+
+impl NameDictionary {
+    pub fn css4() -> Arc<NameDictionary> {
+        CSS4_DICTIONARY.clone()
+    }
+
+    pub fn from_csv(name: &str, csv: &str) -> Result<NameDictionary, PaletteError> {
+        let mut lines = csv
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty());
+        let header = match lines.next() {
+            Some((_, header)) => csv_fields(header),
+            None => return Err(PaletteError::InvalidFormat(format!("{name} is empty"))),
+        };
+        let column = |wanted: &str| {
+            header
+                .iter()
+                .position(|field| field.trim().eq_ignore_ascii_case(wanted))
+                .ok_or_else(|| {
+                    PaletteError::InvalidFormat(format!("{name} has no '{wanted}' column"))
+                })
+        };
+        let (name_column, hex_column) = (column("name")?, column("hex")?);
+
+        let mut entries: Vec<(String, Rgb)> = Vec::new();
+        let mut by_name: HashMap<String, usize> = HashMap::new();
+        for (line_num, line) in lines {
+            let fields = csv_fields(line);
+            let (Some(color_name), Some(hex)) = (fields.get(name_column), fields.get(hex_column))
+            else {
+                return Err(PaletteError::InvalidFormat(format!(
+                    "Line {}: expected a name and a hex value",
+                    line_num + 1
+                )));
+            };
+            let color_name = color_name.trim();
+            let hex = hex.trim().trim_start_matches('#');
+            let value = match u32::from_str_radix(hex, 16) {
+                Ok(value) if hex.len() == 6 => value,
+                _ => {
+                    return Err(PaletteError::InvalidFormat(format!(
+                        "Line {}: '{hex}' is not a 6 digit hex color",
+                        line_num + 1
+                    )));
+                }
+            };
+
+            // some published lists repeat a name, keep the first
+            let key = name_key(color_name);
+            if by_name.contains_key(&key) {
+                continue;
+            }
+            by_name.insert(key, entries.len());
+            let rgb = Rgb::new((value >> 16) as u8, (value >> 8) as u8, value as u8);
+            entries.push((color_name.to_string(), rgb));
+        }
+
+        Ok(NameDictionary {
+            name: name.to_string(),
+            index: NearestColor::new(entries.iter().map(|(_, rgb)| rgb)),
+            entries,
+            by_name,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Rgb)> {
+        self.entries.iter().map(|(name, rgb)| (name.as_str(), rgb))
+    }
+
+    /// Case and spacing don't matter, "indianred" finds "Indian Red". Returns the
+    /// name as the dictionary spells it.
+    pub fn get(&self, name: &str) -> Option<(&str, &Rgb)> {
+        self.by_name
+            .get(&name_key(name))
+            .map(|&i| (self.entries[i].0.as_str(), &self.entries[i].1))
+    }
+
+    /// Up to `n` of the closest names and their distance in Oklab, nearest first.
+    pub fn nearest_n(&self, goal: &Rgb, n: usize) -> Vec<(&str, f32)> {
+        self.index
+            .nearest(goal, n)
+            .into_iter()
+            .map(|(i, distance)| (self.entries[i].0.as_str(), distance))
+            .collect()
+    }
+
+    /// The closest name, an exact match wins outright. Metrics other than Oklab
+    /// choose among the few closest in Oklab rather than the whole dictionary.
+    pub fn nearest(&self, goal: &Rgb, metric: DistanceMetric) -> Result<&str, PaletteError> {
+        let goal = Rgb::new(goal.r, goal.g, goal.b);
+        let candidates = match metric {
+            DistanceMetric::Oklab => 1,
+            _ => CANDIDATES,
+        };
+
+        let mut nearest = None;
+        let mut min_distance = f32::MAX;
+        for (i, _) in self.index.nearest(&goal, candidates) {
+            let (name, rgb) = &self.entries[i];
+            if *rgb == goal {
+                return Ok(name);
+            }
+            let distance = metric.distance_rgb(&goal, rgb)?;
+            if distance < min_distance {
+                min_distance = distance;
+                nearest = Some(name.as_str());
+            }
+        }
+        nearest.ok_or_else(|| PaletteError::Display(format!("{} has no colors", self.name)))
+    }
+}
+
+/// The dictionary names are looked up in and given from.
+pub fn active() -> Arc<NameDictionary> {
+    match ACTIVE.read() {
+        Ok(active) => active.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+pub fn set_active(dictionary: Arc<NameDictionary>) {
+    match ACTIVE.write() {
+        Ok(mut active) => *active = dictionary,
+        Err(poisoned) => *poisoned.into_inner() = dictionary,
+    }
+}
+
+/// Looks a name up in the active dictionary, then in CSS4 so css names always parse.
+pub fn find(name: &str) -> Option<(String, Rgb)> {
+    let lookup = |dictionary: &NameDictionary| {
+        dictionary
+            .get(name)
+            .map(|(name, rgb)| (name.to_string(), rgb.clone()))
+    };
+    lookup(&active()).or_else(|| lookup(&NameDictionary::css4()))
+}
+
+// names that differ only in case and spacing are the same name
+fn name_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// commas split fields except inside double quotes, "" is a literal quote
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_dictionary_matches_css() {
+        let css4 = NameDictionary::css4();
+        let tests = [
+            ("Pink", Rgb::new(255, 192, 203)),
+            ("Peru", Rgb::new(205, 133, 63)),
+            ("Medium Sea Green", Rgb::new(60, 179, 113)),
+            ("Cornflower Blue", Rgb::new(100, 149, 237)),
+            ("Olive", Rgb::new(128, 128, 0)),
+            ("rebeccapurple", Rgb::new(102, 51, 153)),
+        ];
+
+        for (name, expected) in tests {
+            println!("input: {:?}, desired result: {:?}", name, expected);
+            match css4.get(name) {
+                Some((_, rgb)) => assert_eq!(*rgb, expected),
+                None => panic!("{name} is missing"),
+            }
+        }
+
+        let mut names: Vec<String> = css4.iter().map(|(name, _)| name.to_lowercase()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), css4.len());
+    }
+
+    #[test]
+    fn loads_other_dictionaries() {
+        let csv = "\
+Hex,Name,Notes
+#1f4e79,Brand Navy,\"primary, dark\"
+FFB000,Signal Amber,
+1F4E79,brand navy,repeated
+";
+        let brand = NameDictionary::from_csv("brand", csv).unwrap();
+        assert_eq!(brand.len(), 2);
+        assert_eq!(
+            brand.get("BRANDNAVY"),
+            Some(("Brand Navy", &Rgb::new(31, 78, 121)))
+        );
+        assert_eq!(
+            brand
+                .nearest(&Rgb::new(250, 170, 10), DistanceMetric::default())
+                .unwrap(),
+            "Signal Amber"
+        );
+
+        let near = brand.nearest_n(&Rgb::new(31, 78, 121), 5);
+        assert_eq!(near.len(), 2);
+        assert_eq!(near[0], ("Brand Navy", 0.0));
+        assert_eq!(near[1].0, "Signal Amber");
+
+        assert!(matches!(
+            NameDictionary::from_csv("bad", "name,hex\nRed,F00\n"),
+            Err(PaletteError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            NameDictionary::from_csv("bad", "name,rgb\nRed,255 0 0\n"),
+            Err(PaletteError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn large_dictionaries_load() {
+        let mut csv = String::from("name,hex\n");
+        for i in 0..20_000u32 {
+            csv.push_str(&format!("Color {i},{:06x}\n", i * 811 % 0x1000000));
+        }
+        // every name again, differently spaced
+        for i in 0..20_000u32 {
+            csv.push_str(&format!("color{i},000000\n"));
+        }
+        let large = NameDictionary::from_csv("large", &csv).unwrap();
+        assert_eq!(large.len(), 20_000);
+        assert_eq!(
+            large.get("COLOR 19999"),
+            Some(("Color 19999", &Rgb::new(0xF7, 0x7C, 0x35)))
+        );
+    }
+}
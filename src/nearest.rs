@@ -0,0 +1,192 @@
+use crate::{
+    color_spaces::Rgb,
+    encoding::{linear_srgb_to_oklab, srgb8_to_linear},
+};
+
+/// Nearest neighbour search over a fixed set of colors. The colors are kept as a
+/// k-d tree in Oklab, so a lookup visits a handful of them instead of all.
+/// Distances are Euclidean in Oklab on the 0-100 scale `DistanceMetric::Oklab` uses.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct NearestColor {
+    // a balanced tree laid out in place, each slice's median splits it on the
+    // axis for its depth
+    points: Vec<Point>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Point {
+    lab: [f32; 3],
+    // position in the colors the index was built from
+    index: usize,
+}
+
+impl NearestColor {
+    pub fn new<'a>(colors: impl IntoIterator<Item = &'a Rgb>) -> NearestColor {
+        let mut points: Vec<Point> = colors
+            .into_iter()
+            .enumerate()
+            .map(|(index, rgb)| Point {
+                lab: oklab(rgb),
+                index,
+            })
+            .collect();
+        build(&mut points, 0);
+        NearestColor { points }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Up to `n` of the closest colors as (position, distance), nearest first. Equal
+    /// distances keep the order the colors were given in.
+    pub fn nearest(&self, goal: &Rgb, n: usize) -> Vec<(usize, f32)> {
+        let mut found: Vec<(f32, usize)> = Vec::with_capacity(n + 1);
+        if n > 0 {
+            search(&self.points, 0, &oklab(goal), n, &mut found);
+        }
+        found
+            .into_iter()
+            .map(|(distance_squared, index)| (index, distance_squared.sqrt() * 100.0))
+            .collect()
+    }
+}
+
+fn oklab(rgb: &Rgb) -> [f32; 3] {
+    let (l, a, b) = linear_srgb_to_oklab(
+        srgb8_to_linear(rgb.r),
+        srgb8_to_linear(rgb.g),
+        srgb8_to_linear(rgb.b),
+    );
+    [l, a, b]
+}
+
+fn build(points: &mut [Point], depth: usize) {
+    if points.len() <= 1 {
+        return;
+    }
+    let axis = depth % 3;
+    let mid = points.len() / 2;
+    points.select_nth_unstable_by(mid, |p, q| p.lab[axis].total_cmp(&q.lab[axis]));
+    let (below, rest) = points.split_at_mut(mid);
+    build(below, depth + 1);
+    build(&mut rest[1..], depth + 1);
+}
+
+// found stays sorted by (distance squared, index) and no longer than n
+fn search(
+    points: &[Point],
+    depth: usize,
+    goal: &[f32; 3],
+    n: usize,
+    found: &mut Vec<(f32, usize)>,
+) {
+    if points.is_empty() {
+        return;
+    }
+    let mid = points.len() / 2;
+    let point = &points[mid];
+
+    let distance: f32 = (0..3).map(|i| (point.lab[i] - goal[i]).powi(2)).sum();
+    let candidate = (distance, point.index);
+    let position = found.partition_point(|kept| {
+        kept.0 < candidate.0 || (kept.0 == candidate.0 && kept.1 < candidate.1)
+    });
+    if position < n {
+        found.insert(position, candidate);
+        found.truncate(n);
+    }
+
+    let axis = depth % 3;
+    let offset = goal[axis] - point.lab[axis];
+    let (near, far) = if offset < 0.0 {
+        (&points[..mid], &points[mid + 1..])
+    } else {
+        (&points[mid + 1..], &points[..mid])
+    };
+    search(near, depth + 1, goal, n, found);
+    // the other side can only help if the splitting plane is closer than the worst kept
+    if found.len() < n || offset * offset <= found[found.len() - 1].0 {
+        search(far, depth + 1, goal, n, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force(colors: &[Rgb], goal: &Rgb, n: usize) -> Vec<usize> {
+        let goal = oklab(goal);
+        let mut distances: Vec<(f32, usize)> = colors
+            .iter()
+            .enumerate()
+            .map(|(i, rgb)| {
+                let lab = oklab(rgb);
+                ((0..3).map(|axis| (lab[axis] - goal[axis]).powi(2)).sum(), i)
+            })
+            .collect();
+        distances.sort_by(|p, q| p.0.total_cmp(&q.0).then(p.1.cmp(&q.1)));
+        distances.into_iter().take(n).map(|(_, i)| i).collect()
+    }
+
+    #[test]
+    fn matches_a_linear_scan() {
+        let colors: Vec<Rgb> = (0..=255u32)
+            .step_by(15)
+            .flat_map(|r| {
+                (0..=255u32)
+                    .step_by(51)
+                    .map(move |g| Rgb::new(r as u8, g as u8, ((r * 7 + g * 3) % 256) as u8))
+            })
+            .collect();
+        let index = NearestColor::new(&colors);
+        assert_eq!(index.len(), colors.len());
+
+        for goal in [
+            Rgb::new(205, 92, 92),
+            Rgb::new(0, 0, 0),
+            Rgb::new(255, 255, 255),
+            Rgb::new(18, 200, 77),
+            Rgb::new(120, 120, 240),
+        ] {
+            println!("input: {:?}", goal);
+            let found: Vec<usize> = index
+                .nearest(&goal, 5)
+                .into_iter()
+                .map(|(i, _)| i)
+                .collect();
+            assert_eq!(found, brute_force(&colors, &goal, 5));
+        }
+    }
+
+    #[test]
+    fn nearest_first_with_distances() {
+        let colors = [
+            Rgb::new(255, 0, 0),
+            Rgb::new(0, 0, 255),
+            Rgb::new(250, 10, 10),
+            Rgb::new(255, 0, 0),
+        ];
+        let index = NearestColor::new(&colors);
+
+        let found = index.nearest(&Rgb::new(255, 0, 0), 3);
+        assert_eq!(found.len(), 3);
+        // the exact match and its duplicate, in the order given
+        assert_eq!((found[0].0, found[1].0), (0, 3));
+        assert_eq!(found[0].1, 0.0);
+        assert_eq!(found[2].0, 2);
+        assert!(found[2].1 > 0.0);
+
+        assert_eq!(index.nearest(&Rgb::new(0, 0, 0), 10).len(), 4);
+        assert!(index.nearest(&Rgb::new(0, 0, 0), 0).is_empty());
+        assert!(
+            NearestColor::default()
+                .nearest(&Rgb::new(0, 0, 0), 3)
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{
+    color_distance::{self, dedupe},
+    color_math::{
+        Harmony, WorkingSpace, generate_color, harmony, n_color_average_complement, turn_hue,
+    },
+    color_spaces::Color,
+    error::PaletteError,
+};
+use rand::prelude::*;
+use std::{
+    fmt,
+    sync::{Arc, LazyLock, RwLock},
+};
+
+static REGISTRY: LazyLock<RwLock<Vec<Arc<dyn PaletteStrategy>>>> = LazyLock::new(|| {
+    let mut builtin: Vec<Arc<dyn PaletteStrategy>> = vec![Arc::new(RandomHarmony)];
+    for harmony in Harmony::ALL {
+        builtin.push(Arc::new(HarmonyStrategy(harmony)));
+    }
+    builtin.push(Arc::new(Monochrome));
+    builtin.push(Arc::new(Pastel));
+    RwLock::new(builtin)
+});
+
+/// A way of coming up with new colors for a palette. Implement it and `register` it
+/// to offer another one alongside the built in strategies.
+pub trait PaletteStrategy: fmt::Debug + Send + Sync {
+    /// What it's listed as, also what `find` looks it up by.
+    fn name(&self) -> &str;
+
+    /// `count` new colors to go with `base`, the colors being kept. `base` is empty
+    /// when the whole palette is new. Every random choice should come from `rng`, so
+    /// a seed gives back the same palette.
+    fn generate(
+        &self,
+        base: &[Color],
+        count: usize,
+        space: WorkingSpace,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<Color>, PaletteError>;
+}
+
+/// Every strategy, the built in ones first and then those registered in order.
+pub fn strategies() -> Vec<Arc<dyn PaletteStrategy>> {
+    match REGISTRY.read() {
+        Ok(registry) => registry.clone(),
+        Err(poisoned) => poisoned.into_inner().clone(),
+    }
+}
+
+/// Adds a strategy to the end of the list, or replaces one with the same name.
+pub fn register(strategy: Arc<dyn PaletteStrategy>) {
+    let mut registry = match REGISTRY.write() {
+        Ok(registry) => registry,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match registry
+        .iter_mut()
+        .find(|existing| existing.name().eq_ignore_ascii_case(strategy.name()))
+    {
+        Some(existing) => *existing = strategy,
+        None => registry.push(strategy),
+    }
+}
+
+/// Case doesn't matter, "pastel" finds "Pastel".
+pub fn find(name: &str) -> Option<Arc<dyn PaletteStrategy>> {
+    strategies()
+        .into_iter()
+        .find(|strategy| strategy.name().eq_ignore_ascii_case(name))
+}
+
+// under this by the chosen metric two colors look the same
+const TOO_SIMILAR: f32 = 3.0;
+// how many times missing colors are asked for again before taking what comes
+const ATTEMPTS: usize = 4;
+
+/// `strategy`'s colors with any that look the same as another, or as one in `base`,
+/// asked for again. A strategy that keeps repeating itself still fills the palette.
+pub fn generate_distinct(
+    strategy: &dyn PaletteStrategy,
+    base: &[Color],
+    count: usize,
+    space: WorkingSpace,
+    rng: &mut dyn RngCore,
+) -> Result<Vec<Color>, PaletteError> {
+    let metric = color_distance::metric();
+    // kept colors never come close to these, so deduping again leaves them first
+    let anchors = dedupe(base, metric, TOO_SIMILAR)?;
+    let mut kept: Vec<Color> = Vec::with_capacity(count);
+    let mut fresh = strategy.generate(base, count, space, rng)?;
+
+    for _ in 0..ATTEMPTS {
+        let known = anchors.len() + kept.len();
+        let unique = dedupe(&[&anchors[..], &kept, &fresh].concat(), metric, TOO_SIMILAR)?;
+        kept.extend(unique.into_iter().skip(known));
+        if kept.len() >= count {
+            break;
+        }
+        fresh = strategy.generate(&[base, &kept].concat(), count - kept.len(), space, rng)?;
+    }
+
+    let missing = count.saturating_sub(kept.len());
+    kept.extend(fresh.into_iter().take(missing));
+    kept.truncate(count);
+    Ok(kept)
+}
+
+// -----------------------
+
+/// Picks at random between complements, triads, squares, the average complement and
+/// entirely new colors. Hues are turned in the working space.
+#[derive(Debug, Clone, Copy)]
+pub struct RandomHarmony;
+
+impl PaletteStrategy for RandomHarmony {
+    fn name(&self) -> &str {
+        "Random"
+    }
+
+    fn generate(
+        &self,
+        base: &[Color],
+        count: usize,
+        space: WorkingSpace,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Vec<Color>, PaletteError> {
+        let mut palette = base.to_vec();
+        let target = base.len() + count;
+        if base.is_empty() && count > 0 {
+            palette.push(generate_color(space, &mut rng)?);
+            if count > 1 {
+                palette.extend(harmony(&palette[0], Harmony::Complementary, space)?);
+            }
+        }
+
+        while palette.len() < target {
+            // only the methods that fit in what's left, triads add two and squares three
+            let methods = (target - palette.len()).min(3) + 2;
+            let method = rng.random_range(0..methods);
+            let index = rng.random_range(0..palette.len());
+            match method {
+                0 => {
+                    palette.extend(harmony(&palette[index], Harmony::Complementary, space)?);
+                }
+                1 => {
+                    palette.push(generate_color(space, &mut rng)?);
+                }
+                2 => {
+                    let new_color = n_color_average_complement(&palette, space)?;
+                    palette.push(new_color);
+                }
+                3 => {
+                    palette.extend(harmony(&palette[index], Harmony::Triadic, space)?);
+                }
+                _ => {
+                    palette.extend(harmony(&palette[index], Harmony::Square, space)?);
+                }
+            }
+        }
+
+        Ok(palette.split_off(base.len()))
+    }
+}
+
+/// The partners a harmony rule gives the first color, then the same again lighter,
+/// then darker, until there are enough.
+#[derive(Debug, Clone, Copy)]
+pub struct HarmonyStrategy(pub Harmony);
+
+impl PaletteStrategy for HarmonyStrategy {
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+
+    fn generate(
+        &self,
+        base: &[Color],
+        count: usize,
+        space: WorkingSpace,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<Color>, PaletteError> {
+        around_hue(base, count, space, rng, &self.0.offsets())
+    }
+}
+
+/// The first color's hue and saturation at evenly spread lightnesses.
+#[derive(Debug, Clone, Copy)]
+pub struct Monochrome;
+
+impl PaletteStrategy for Monochrome {
+    fn name(&self) -> &str {
+        "Monochrome"
+    }
+
+    fn generate(
+        &self,
+        base: &[Color],
+        count: usize,
+        space: WorkingSpace,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Vec<Color>, PaletteError> {
+        let anchor = match base.first() {
+            Some(color) => color.clone(),
+            None => generate_color(space, &mut rng)?,
+        };
+        let (h, s, _) = space.components(&anchor)?;
+
+        // between near black and near white, leaving out both ends
+        (1..=count)
+            .map(|i| {
+                let l = 100 + 800 * i / (count + 1);
+                Color::new(space.encode(h, s, l as u16).with_alpha(anchor.alpha()))
+            })
+            .collect()
+    }
+}
+
+/// Light, soft colors of any hue.
+#[derive(Debug, Clone, Copy)]
+pub struct Pastel;
+
+impl PaletteStrategy for Pastel {
+    fn name(&self) -> &str {
+        "Pastel"
+    }
+
+    fn generate(
+        &self,
+        _base: &[Color],
+        count: usize,
+        space: WorkingSpace,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<Color>, PaletteError> {
+        (0..count)
+            .map(|_| {
+                let h = rng.random_range(0..360);
+                let s = rng.random_range(300..700);
+                let l = rng.random_range(780..900);
+                Color::new(space.encode(h, s, l))
+            })
+            .collect()
+    }
+}
+
+// the working space is where hues are turned, so steps look even in the perceptual ones
+const LIGHTNESS_STEP: i32 = 150;
+
+// the hues at `offsets` from the first color, then the same again lighter, then
+// darker, and so on until there are enough
+fn around_hue(
+    base: &[Color],
+    count: usize,
+    space: WorkingSpace,
+    mut rng: &mut dyn RngCore,
+    offsets: &[u16],
+) -> Result<Vec<Color>, PaletteError> {
+    let mut colors = Vec::with_capacity(count);
+    let anchor = match base.first() {
+        Some(color) => color.clone(),
+        None if count == 0 => return Ok(colors),
+        None => {
+            let color = generate_color(space, &mut rng)?;
+            colors.push(color.clone());
+            color
+        }
+    };
+    // the anchor's own hue comes round again with each new lightness
+    let hues: Vec<u16> = [0].iter().chain(offsets).copied().collect();
+    for k in 1.. {
+        if colors.len() == count {
+            break;
+        }
+        let round = (k / hues.len()) as i32;
+        let shift = match round % 2 {
+            0 => -LIGHTNESS_STEP * round / 2,
+            _ => LIGHTNESS_STEP * (round + 1) / 2,
+        };
+        colors.push(turn_hue(&anchor, hues[k % hues.len()], shift, space)?);
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color_math::PaletteRng, color_spaces::Rgb};
+
+    #[test]
+    fn every_strategy_fills_the_palette() {
+        let base = [Color::new(Rgb::new(70, 130, 180).encode()).unwrap()];
+        for strategy in strategies() {
+            for count in 0..7 {
+                println!("input: {}, count {count}", strategy.name());
+                for base in [&base[..], &[]] {
+                    let colors = strategy
+                        .generate(base, count, WorkingSpace::Hsl, &mut PaletteRng::seeded(7))
+                        .unwrap();
+                    assert_eq!(colors.len(), count);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hues_follow_the_first_color() {
+        let steel_blue = [Color::new(Rgb::new(70, 130, 180).encode()).unwrap()];
+        let hue = |color: &Color| color.hsl().h;
+        let tests: [(&dyn PaletteStrategy, Vec<u16>); 5] = [
+            (
+                &HarmonyStrategy(Harmony::Analogous { spread: 30 }),
+                vec![177, 237, 207, 177, 237],
+            ),
+            (
+                &HarmonyStrategy(Harmony::SplitComplementary { spread: 30 }),
+                vec![357, 57, 207, 357],
+            ),
+            (
+                &HarmonyStrategy(Harmony::Rectangle { spread: 60 }),
+                vec![267, 27, 87, 207],
+            ),
+            (
+                &HarmonyStrategy(Harmony::Compound { spread: 45 }),
+                vec![252, 27, 342],
+            ),
+            (&Monochrome, vec![207, 207, 207]),
+        ];
+
+        for (strategy, expected) in tests {
+            println!("input: {}, desired result: {:?}", strategy.name(), expected);
+            let colors = strategy
+                .generate(
+                    &steel_blue,
+                    expected.len(),
+                    WorkingSpace::Hsl,
+                    &mut PaletteRng::seeded(7),
+                )
+                .unwrap();
+            let hues: Vec<u16> = colors.iter().map(hue).collect();
+            for (found, wanted) in hues.iter().zip(&expected) {
+                assert!(found.abs_diff(*wanted) <= 1, "{hues:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn random_harmony_turns_hue_in_the_working_space() {
+        for space in [WorkingSpace::Hsl, WorkingSpace::Hsluv, WorkingSpace::Oklch] {
+            println!("input: {space}, desired result: a complement 180 degrees round");
+            // the first two colors of a new palette are a color and its complement
+            let colors = RandomHarmony
+                .generate(&[], 2, space, &mut PaletteRng::seeded(1))
+                .unwrap();
+            let (h1, _, _) = space.components(&colors[0]).unwrap();
+            let (h2, _, _) = space.components(&colors[1]).unwrap();
+            let turned = (h2 as i32 - h1 as i32).rem_euclid(360);
+            assert!(turned.abs_diff(180) <= 2, "{h1} {h2}");
+        }
+    }
+
+    #[test]
+    fn distinct_palettes_have_no_twins() {
+        // harmonies follow the first color wherever it goes, a near black one only
+        // gives near blacks, so just the strategies that draw every color are checked
+        let tests: [&dyn PaletteStrategy; 3] = [&RandomHarmony, &Monochrome, &Pastel];
+        for strategy in tests {
+            for seed in [1, 7, 12345] {
+                println!(
+                    "input: {}, seed {seed}, desired result: no twins",
+                    strategy.name()
+                );
+                let colors = generate_distinct(
+                    strategy,
+                    &[],
+                    6,
+                    WorkingSpace::Hsl,
+                    &mut PaletteRng::seeded(seed),
+                )
+                .unwrap();
+                assert_eq!(colors.len(), 6);
+                let unique = dedupe(&colors, color_distance::metric(), TOO_SIMILAR).unwrap();
+                assert_eq!(unique.len(), colors.len());
+            }
+        }
+
+        // nothing but red to give, it still fills the palette
+        let colors = generate_distinct(
+            &AllRed,
+            &[],
+            3,
+            WorkingSpace::Hsl,
+            &mut PaletteRng::seeded(1),
+        )
+        .unwrap();
+        assert_eq!(colors.len(), 3);
+    }
+
+    #[derive(Debug)]
+    struct AllRed;
+
+    impl PaletteStrategy for AllRed {
+        fn name(&self) -> &str {
+            "All red"
+        }
+
+        fn generate(
+            &self,
+            _base: &[Color],
+            count: usize,
+            _space: WorkingSpace,
+            _rng: &mut dyn RngCore,
+        ) -> Result<Vec<Color>, PaletteError> {
+            (0..count)
+                .map(|_| Color::new(Rgb::new(255, 0, 0).encode()))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn registered_strategies_are_found() {
+        let builtin = strategies().len();
+        register(Arc::new(AllRed));
+        register(Arc::new(AllRed));
+        assert_eq!(strategies().len(), builtin + 1);
+
+        let red = find("all RED").unwrap();
+        let colors = red
+            .generate(&[], 2, WorkingSpace::Hsl, &mut PaletteRng::seeded(1))
+            .unwrap();
+        assert_eq!(*colors[1].rgb(), Rgb::new(255, 0, 0));
+        assert!(find("Pastel").is_some());
+        assert!(find("Plaid").is_none());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::color_spaces::Rgb;
+use std::env;
+
+/// How many colors the terminal can draw. Anything short of truecolor gets each
+/// color quantized to the nearest palette index before it is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSupport {
+    #[default]
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorSupport {
+    pub fn detect() -> ColorSupport {
+        ColorSupport::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    // COLORTERM is the only reliable truecolor signal, TERM names over promise
+    // less often than they under promise, so fall back to the 16 base colors
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorSupport {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            return ColorSupport::TrueColor;
+        }
+        match term {
+            Some(term) if term.ends_with("-direct") => ColorSupport::TrueColor,
+            Some(term) if term.contains("256color") => ColorSupport::Ansi256,
+            _ => ColorSupport::Ansi16,
+        }
+    }
+
+    pub fn ratatui_color(&self, rgb: &Rgb) -> ratatui::style::Color {
+        let truecolor = ratatui::style::Color::Rgb(rgb.r, rgb.g, rgb.b);
+        let quantized = match self {
+            ColorSupport::TrueColor => return truecolor,
+            ColorSupport::Ansi256 => rgb.encode().get_ansi256(),
+            ColorSupport::Ansi16 => rgb.encode().get_ansi16(),
+        };
+        quantized.map_or(truecolor, |ansi| ratatui::style::Color::Indexed(ansi.i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_support_from_env() {
+        let tests = [
+            (
+                (Some("truecolor"), Some("xterm-256color")),
+                ColorSupport::TrueColor,
+            ),
+            ((Some("24bit"), None), ColorSupport::TrueColor),
+            ((None, Some("xterm-direct")), ColorSupport::TrueColor),
+            ((None, Some("tmux-256color")), ColorSupport::Ansi256),
+            ((Some(""), Some("screen-256color")), ColorSupport::Ansi256),
+            ((None, Some("xterm")), ColorSupport::Ansi16),
+            ((None, Some("linux")), ColorSupport::Ansi16),
+            ((None, None), ColorSupport::Ansi16),
+        ];
+
+        for ((colorterm, term), expected) in tests {
+            println!(
+                "input: {:?} {:?}, desired result: {:?}",
+                colorterm, term, expected
+            );
+            assert_eq!(ColorSupport::from_env(colorterm, term), expected);
+        }
+    }
+
+    #[test]
+    fn quantizes_below_truecolor() {
+        let rgb = Rgb::new(255, 0, 0);
+        assert_eq!(
+            ColorSupport::TrueColor.ratatui_color(&rgb),
+            ratatui::style::Color::Rgb(255, 0, 0)
+        );
+        assert_eq!(
+            ColorSupport::Ansi256.ratatui_color(&rgb),
+            ratatui::style::Color::Indexed(196)
+        );
+        assert_eq!(
+            ColorSupport::Ansi16.ratatui_color(&rgb),
+            ratatui::style::Color::Indexed(9)
+        );
+    }
+}
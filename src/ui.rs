@@ -1,5 +1,13 @@
 use crate::{
-    app::App, color_spaces::Color as dis_color, error::PaletteError, input::TextInput, mode::UiMode,
+    app::App,
+    color_distance,
+    color_math::{ApcaLevel, WcagLevel, apca_contrast, contrast_ratio},
+    color_spaces::{Color as dis_color, Rgb},
+    error::PaletteError,
+    input::TextInput,
+    mode::UiMode,
+    named_colors,
+    terminal::ColorSupport,
 };
 use ratatui::{
     Frame,
@@ -10,12 +18,114 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{Block, Borders, Clear, Paragraph, Widget},
 };
+use std::fmt;
+
+/// What translucent colors are composited over before they reach the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backdrop {
+    #[default]
+    Checker,
+    Black,
+    White,
+}
+
+impl Backdrop {
+    pub fn next(self) -> Backdrop {
+        match self {
+            Backdrop::Checker => Backdrop::Black,
+            Backdrop::Black => Backdrop::White,
+            Backdrop::White => Backdrop::Checker,
+        }
+    }
+
+    pub fn color_at(&self, x: u16, y: u16) -> Rgb {
+        match self {
+            // cells are about twice as tall as wide, so squares are two cells across
+            Backdrop::Checker if (x / 2 + y).is_multiple_of(2) => Rgb::new(204, 204, 204),
+            Backdrop::Checker | Backdrop::White => Rgb::new(255, 255, 255),
+            Backdrop::Black => Rgb::new(0, 0, 0),
+        }
+    }
+}
+
+/// Which names are written under each color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NameStyle {
+    #[default]
+    Hidden,
+    Dictionary,
+    Descriptive,
+    Both,
+}
+
+impl NameStyle {
+    pub fn next(self) -> NameStyle {
+        match self {
+            NameStyle::Hidden => NameStyle::Dictionary,
+            NameStyle::Dictionary => NameStyle::Descriptive,
+            NameStyle::Descriptive => NameStyle::Both,
+            NameStyle::Both => NameStyle::Hidden,
+        }
+    }
+
+    fn lines(self, color: &dis_color) -> Vec<String> {
+        let (name, description) = (color.name(), color.description());
+        match self {
+            NameStyle::Hidden => vec![],
+            NameStyle::Dictionary => vec![name.to_string()],
+            NameStyle::Descriptive => vec![description.to_string()],
+            // a name that fell back to the description would only repeat it
+            NameStyle::Both if name == description => vec![name.to_string()],
+            NameStyle::Both => vec![name.to_string(), description.to_string()],
+        }
+    }
+}
+
+impl fmt::Display for NameStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameStyle::Hidden => write!(f, "Hidden"),
+            NameStyle::Dictionary => write!(f, "Dictionary"),
+            NameStyle::Descriptive => write!(f, "Descriptive"),
+            NameStyle::Both => write!(f, "Both"),
+        }
+    }
+}
 
 impl Widget for &App {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let block = Block::bordered()
             .title(" Palette Generator ".bold())
-            .title_bottom(Line::from(vec![" Quit ".into(), "<Q> ".blue().bold()]).centered())
+            .title_bottom(
+                Line::from(vec![
+                    " Working space ".into(),
+                    format!("<W> {} ", self.working_space).blue().bold(),
+                    " Names ".into(),
+                    format!("<D> {} ", named_colors::active().name)
+                        .blue()
+                        .bold(),
+                    " Metric ".into(),
+                    format!("<M> {} ", color_distance::metric()).blue().bold(),
+                    " Strategy ".into(),
+                    format!("<G> {} ", self.palette_strategy().name())
+                        .blue()
+                        .bold(),
+                    " Harmony ".into(),
+                    format!("<H> {} ", self.harmony.map_or("-", |rule| rule.name()))
+                        .blue()
+                        .bold(),
+                    " Seed ".into(),
+                    match self.seed {
+                        Some(seed) => format!("{seed} ").blue().bold(),
+                        None => "- ".blue().bold(),
+                    },
+                    " Labels ".into(),
+                    format!("<N> {} ", self.name_style).blue().bold(),
+                    " Quit ".into(),
+                    "<Q> ".blue().bold(),
+                ])
+                .centered(),
+            )
             .border_set(border::THICK);
 
         let inner = block.inner(area);
@@ -39,12 +149,84 @@ impl Widget for &App {
                     options,
                     selected,
                 } if *column == i => {
-                    render_monochrome_column(options, *selected, *column_area, buf);
+                    let rows: Vec<(&str, &dis_color)> =
+                        options.iter().map(|color| ("", color)).collect();
+                    render_options_column(
+                        &rows,
+                        *selected,
+                        *column_area,
+                        buf,
+                        self.backdrop,
+                        self.color_support,
+                    );
+                }
+                UiMode::Explorer {
+                    column,
+                    grid,
+                    row,
+                    col,
+                    ..
+                } if *column == i => {
+                    render_explorer_column(
+                        grid,
+                        (*row, *col),
+                        *column_area,
+                        buf,
+                        self.backdrop,
+                        self.color_support,
+                    );
+                }
+                UiMode::Scale {
+                    column,
+                    scale,
+                    selected,
+                } if *column == i => {
+                    let steps: Vec<String> =
+                        scale.iter().map(|(step, _)| step.to_string()).collect();
+                    let rows: Vec<(&str, &dis_color)> = steps
+                        .iter()
+                        .zip(scale)
+                        .map(|(step, (_, color))| (step.as_str(), color))
+                        .collect();
+                    render_options_column(
+                        &rows,
+                        *selected,
+                        *column_area,
+                        buf,
+                        self.backdrop,
+                        self.color_support,
+                    );
+                }
+                UiMode::Harmony {
+                    column,
+                    options,
+                    selected,
+                } if *column == i => {
+                    let rows: Vec<(&str, &dis_color)> = options
+                        .iter()
+                        .map(|(rule, color)| (rule.name(), color))
+                        .collect();
+                    render_options_column(
+                        &rows,
+                        *selected,
+                        *column_area,
+                        buf,
+                        self.backdrop,
+                        self.color_support,
+                    );
                 }
                 _ => {
                     let color = self.colors[i].clone();
                     let selected = i == self.selected;
-                    render_color_column(color, *column_area, buf, selected);
+                    render_color_column(
+                        color,
+                        *column_area,
+                        buf,
+                        selected,
+                        self.name_style,
+                        self.backdrop,
+                        self.color_support,
+                    );
                 }
             }
         }
@@ -83,6 +265,90 @@ pub fn draw_save_popup(frame: &mut Frame, input: &TextInput) {
     frame.set_cursor_position((inner.x + input.cursor_col() + 1, inner.y + 1));
 }
 
+pub fn draw_export_popup(frame: &mut Frame, input: &TextInput) {
+    let area = centered_rect(frame.area(), 60, 9);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Export tonal scales as tokens ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(input, inner);
+
+    frame.set_cursor_position((inner.x + input.cursor_col() + 1, inner.y + 1));
+}
+
+// wide enough for "Lc -107.9 content" and "21.00:1 AA large"
+const CONTRAST_CELL: usize = 19;
+
+/// Every color of the palette as text on every other. Rows are the text, columns
+/// the background, each cell drawn in its own pair of colors.
+pub fn draw_contrast_popup(
+    frame: &mut Frame,
+    colors: &[dis_color],
+    apca: bool,
+    support: ColorSupport,
+) {
+    let area = centered_rect(frame.area(), 90, 60);
+    frame.render_widget(Clear, area);
+
+    let title = if apca {
+        " Contrast, APCA Lc <A> "
+    } else {
+        " Contrast, WCAG 2.1 <A> "
+    };
+    let block = Block::default().borders(Borders::ALL).title(title);
+
+    let mut header = vec![Span::raw(format!("{:<10}", "text"))];
+    header.extend(
+        colors.iter().map(|background| {
+            Span::raw(format!("{:^CONTRAST_CELL$}", background.hex().to_string()))
+        }),
+    );
+    let mut lines = vec![Line::from(header), Line::from("")];
+    for text in colors {
+        let mut row = vec![Span::raw(format!("{:<10}", text.hex().to_string()))];
+        for background in colors {
+            let cell = if apca {
+                let lc = apca_contrast(text, background);
+                format!("Lc {lc:.1} {}", ApcaLevel::from_lc(lc))
+            } else {
+                let ratio = contrast_ratio(text, background);
+                format!("{ratio:.2}:1 {}", WcagLevel::from_ratio(ratio))
+            };
+            row.push(Span::styled(
+                format!("{cell:^CONTRAST_CELL$}"),
+                Style::default()
+                    .fg(support.ratatui_color(text.rgb()))
+                    .bg(support.ratatui_color(background.rgb())),
+            ));
+        }
+        lines.push(Line::from(row));
+    }
+    lines.push(Line::from(""));
+    lines.push(
+        Line::from("Press A to switch measures, Esc to close")
+            .style(Style::default().fg(Color::Gray)),
+    );
+
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+pub fn draw_edit_popup(frame: &mut Frame, input: &TextInput) {
+    let area = centered_rect(frame.area(), 60, 9);
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Edit color (hex, rgb(), hsl(), oklch(), name...) ");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(input, inner);
+
+    frame.set_cursor_position((inner.x + input.cursor_col() + 1, inner.y + 1));
+}
+
 pub fn draw_open_popup(frame: &mut Frame, input: &TextInput, matches: &[String], selected: usize) {
     let area = centered_rect(frame.area(), 60, 40);
     frame.render_widget(Clear, area);
@@ -137,21 +403,44 @@ fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
     horizontal[1]
 }
 
-fn render_color_column(color: dis_color, area: Rect, buf: &mut Buffer, selected: bool) {
+fn paint_backdrop(
+    color: &dis_color,
+    area: Rect,
+    buf: &mut Buffer,
+    backdrop: Backdrop,
+    support: ColorSupport,
+) {
+    if color.is_opaque() {
+        return;
+    }
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.set_bg(color.ratatui_color_over(&backdrop.color_at(x, y), support));
+            }
+        }
+    }
+}
+
+fn render_color_column(
+    color: dis_color,
+    area: Rect,
+    buf: &mut Buffer,
+    selected: bool,
+    name_style: NameStyle,
+    backdrop: Backdrop,
+    support: ColorSupport,
+) {
     let mut style = Style::default()
-        .fg(color.ratatui_text())
-        .bg(color.ratatui_color());
+        .fg(color.ratatui_text(support))
+        .bg(color.ratatui_color_over(&backdrop.color_at(area.x, area.y), support));
 
     if selected {
         style = style.add_modifier(ratatui::style::Modifier::BOLD);
     }
 
     buf.set_style(area, style);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Min(4), Constraint::Length(4)])
-        .split(area);
+    paint_backdrop(&color, area, buf, backdrop, support);
 
     let mut text = Text::from(vec![
         Line::styled("", style),
@@ -163,15 +452,28 @@ fn render_color_column(color: dis_color, area: Rect, buf: &mut Buffer, selected:
             Line::styled(color.hex_to_string(), style),
         ]);
     }
+    for name in name_style.lines(&color) {
+        text.push_line(Line::styled(name, style));
+    }
+    if !color.in_srgb_gamut() {
+        text.push_line(Line::styled("out of sRGB", style));
+    }
+
+    // the label grows with its lines, plus the border around them
+    let label_height = text.lines.len() as u16 + 2;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(4), Constraint::Length(label_height)])
+        .split(area);
 
     let mut block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default())
-        .fg(color.ratatui_color());
+        .fg(color.ratatui_color(support));
     if selected {
         block = block
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(color.ratatui_text()));
+            .border_style(Style::default().fg(color.ratatui_text(support)));
     }
 
     Paragraph::new(text.clone())
@@ -183,35 +485,109 @@ fn render_color_column(color: dis_color, area: Rect, buf: &mut Buffer, selected:
         .render(chunks[1], buf);
 }
 
-fn render_monochrome_column(colors: &[dis_color], selected: usize, area: Rect, buf: &mut Buffer) {
+// the grid fills the column, the highlighted cell's hex sits underneath
+fn render_explorer_column(
+    grid: &[Vec<dis_color>],
+    (selected_row, selected_col): (usize, usize),
+    area: Rect,
+    buf: &mut Buffer,
+    backdrop: Backdrop,
+    support: ColorSupport,
+) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(grid.len() as u16), Constraint::Length(3)])
+        .split(area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Ratio(1, grid.len() as u32); grid.len()])
+        .split(chunks[0]);
+
+    for (i, (colors, row)) in grid.iter().zip(rows.iter()).enumerate() {
+        let cells = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Ratio(1, colors.len() as u32);
+                colors.len()
+            ])
+            .split(*row);
+
+        for (j, (color, cell)) in colors.iter().zip(cells.iter()).enumerate() {
+            let style = Style::default()
+                .bg(color.ratatui_color_over(&backdrop.color_at(cell.x, cell.y), support))
+                .fg(color.ratatui_text(support));
+            let block = if (i, j) == (selected_row, selected_col) {
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default())
+                    .fg(color.ratatui_text(support))
+            } else {
+                Block::default()
+            };
+
+            Paragraph::new("")
+                .block(block)
+                .style(style)
+                .render(*cell, buf);
+            paint_backdrop(color, *cell, buf, backdrop, support);
+        }
+    }
+
+    let Some(color) = grid.get(selected_row).and_then(|row| row.get(selected_col)) else {
+        return;
+    };
+    let style = Style::default()
+        .bg(color.ratatui_color_over(&backdrop.color_at(chunks[1].x, chunks[1].y), support))
+        .fg(color.ratatui_text(support));
+    Paragraph::new(Line::styled(color.hex().to_string(), style))
+        .block(Block::default().borders(Borders::ALL))
+        .style(style)
+        .alignment(ratatui::layout::Alignment::Center)
+        .render(chunks[1], buf);
+    paint_backdrop(color, chunks[1], buf, backdrop, support);
+}
+
+// one row per option, each labelled when there's a label to give
+fn render_options_column(
+    options: &[(&str, &dis_color)],
+    selected: usize,
+    area: Rect,
+    buf: &mut Buffer,
+    backdrop: Backdrop,
+    support: ColorSupport,
+) {
     let rows = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
-            Constraint::Ratio(1, colors.len() as u32);
-            colors.len()
+            Constraint::Ratio(1, options.len() as u32);
+            options.len()
         ])
         .split(area);
 
-    for (i, (color, row)) in colors.iter().zip(rows.iter()).enumerate() {
+    for (i, ((label, color), row)) in options.iter().zip(rows.iter()).enumerate() {
         let base_style = Style::default()
-            .bg(color.ratatui_color())
-            .fg(color.ratatui_text());
+            .bg(color.ratatui_color_over(&backdrop.color_at(row.x, row.y), support))
+            .fg(color.ratatui_text(support));
 
         let block = if i == selected {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default())
-                .fg(color.ratatui_text())
+                .fg(color.ratatui_text(support))
         } else {
             Block::default()
         };
 
-        let text = Line::from(Span::styled(color.hex_to_string(), base_style));
+        let text = match *label {
+            "" => Line::from(Span::styled(color.hex_to_string(), base_style)),
+            label => Line::from(Span::styled(format!("{label} {}", color.hex()), base_style)),
+        };
 
         Paragraph::new(text)
             .block(block)
             .style(base_style)
             .alignment(ratatui::layout::Alignment::Center)
             .render(*row, buf);
+        paint_backdrop(color, *row, buf, backdrop, support);
     }
 }